@@ -0,0 +1,168 @@
+//! Non-negative integers of any size, for sums that outgrow the built-in types.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+/// Each limb holds 9 decimal digits, which keeps printing simple.
+const LIMB_BASE: u64 = 1_000_000_000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    /// Least significant first, without leading zero limbs. Zero has none.
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    /// The number with the given decimal digits, most significant first.
+    pub fn from_digits(digits: &[u8]) -> Self {
+        let mut limbs = Vec::with_capacity(digits.len() / 9 + 1);
+        for chunk in digits.rchunks(9) {
+            let mut limb = 0;
+            for &digit in chunk {
+                if digit > 9 {
+                    panic!("{} is not a decimal digit", digit);
+                }
+                limb = limb * 10 + digit as u32;
+            }
+            limbs.push(limb);
+        }
+        let mut n = BigUint { limbs };
+        n.trim();
+        n
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// The value if it fits in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        let mut value: u128 = 0;
+        for &limb in self.limbs.iter().rev() {
+            value = value
+                .checked_mul(LIMB_BASE as u128)?
+                .checked_add(limb as u128)?;
+        }
+        Some(value)
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut value: u128) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % LIMB_BASE as u128) as u32);
+            value /= LIMB_BASE as u128;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = 0;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + other.limbs.get(i).copied().unwrap_or(0) as u64 + carry;
+            *limb = (sum % LIMB_BASE) as u32;
+            carry = sum / LIMB_BASE;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl Add<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, other: &BigUint) -> BigUint {
+        self += other;
+        self
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((most, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{}", most)?;
+        for limb in rest.iter().rev() {
+            write!(f, "{:09}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_digits() {
+        assert_eq!("0", BigUint::from_digits(&[]).to_string());
+        assert_eq!("0", BigUint::from_digits(&[0, 0, 0]).to_string());
+        assert_eq!(
+            "1000000000",
+            BigUint::from_digits(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0]).to_string()
+        );
+        assert_eq!(
+            "12345678901234567890123",
+            BigUint::from_digits(&[
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3
+            ])
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_add_with_carry() {
+        let mut n = BigUint::from(999_999_999_999_999_999u128);
+        n += &BigUint::from(1);
+        assert_eq!("1000000000000000000", n.to_string());
+        assert_eq!(Some(1_000_000_000_000_000_000), n.to_u128());
+
+        let max = BigUint::from(u128::MAX);
+        let sum = max.clone() + &max;
+        assert_eq!(None, sum.to_u128());
+        assert_eq!("680564733841876926926749214863536422910", sum.to_string());
+        assert!(sum > max);
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(BigUint::from(5) < BigUint::from(1_000_000_000));
+        assert!(BigUint::from(2_000_000_001) > BigUint::from(1_999_999_999));
+        assert!(BigUint::zero().is_zero());
+        assert_eq!(BigUint::zero(), BigUint::from(0));
+    }
+}
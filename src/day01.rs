@@ -1,59 +1,221 @@
-pub fn part1(input: &str) -> i32 {
-    let mut offset = 50;
-    let mut zeroes = 0;
+/// A dial with positions `0..size` that wraps around in both directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dial {
+    size: i64,
+    position: i64,
+}
+
+impl Dial {
+    pub fn new(size: i64, start: i64) -> Self {
+        if size <= 0 {
+            panic!("Dial size must be positive, got {}", size);
+        }
+        Dial {
+            size,
+            position: start.rem_euclid(size),
+        }
+    }
 
-    for line in input.lines() {
-        let dir = &line[0..1];
-        let value: i32 = match line[1..].parse() {
-            Ok(v) => v,
-            Err(_) => panic!("Failed to parse integer from line: {}", &line[1..]),
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    /// How often the dial would point at 0 while turning `clicks` (negative
+    /// to the left), including where it stops.
+    pub fn zero_passes(&self, clicks: i64) -> i64 {
+        // Turning left passes the same zeroes as turning right from the
+        // mirrored position
+        let start = if clicks < 0 {
+            (self.size - self.position) % self.size
+        } else {
+            self.position
         };
+        // Full turns pass 0 once each, reduce first so huge turns cannot overflow
+        let magnitude = clicks.unsigned_abs();
+        let size = self.size as u64;
+        let full_turns = (magnitude / size) as i64;
+        full_turns + (start + (magnitude % size) as i64) / self.size
+    }
 
-        if dir == "L" {
-            offset -= value;
+    /// Turns the dial `clicks` (negative to the left) and returns how often
+    /// it pointed at 0 on the way.
+    pub fn rotate(&mut self, clicks: i64) -> i64 {
+        let passes = self.zero_passes(clicks);
+        self.position = (self.position + clicks.rem_euclid(self.size)) % self.size;
+        passes
+    }
+}
+
+impl Default for Dial {
+    fn default() -> Self {
+        Dial::new(100, 50)
+    }
+}
+
+/// One line of the input.
+struct Rotation {
+    line: usize,
+    left: bool,
+    magnitude: i64,
+}
+
+impl Rotation {
+    /// Signed click count, negative to the left.
+    fn clicks(&self) -> i64 {
+        if self.left {
+            -self.magnitude
         } else {
-            offset += value;
+            self.magnitude
         }
+    }
+}
 
-        offset = offset % 100;
+/// Parses one rotation like `L68`, `r 5` or `R10 # comment`. `None` for lines
+/// with nothing but whitespace or a comment.
+fn parse_rotation(line: &str) -> Result<Option<(bool, i64)>, String> {
+    let line = match line.split_once('#') {
+        Some((before, _)) => before,
+        None => line,
+    };
+    let line = line.trim();
+    let Some(dir) = line.chars().next() else {
+        return Ok(None);
+    };
 
-        if offset == 0 {
-            zeroes += 1;
+    let left = match dir.to_ascii_uppercase() {
+        'L' => true,
+        'R' => false,
+        _ => return Err(format!("unknown direction {:?}, expected L or R", dir)),
+    };
+    let value = line[dir.len_utf8()..].trim_start();
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!(
+            "expected a number of clicks after {}, got {:?}",
+            dir, value
+        ));
+    }
+    match value.parse() {
+        Ok(magnitude) => Ok(Some((left, magnitude))),
+        Err(_) => Err(format!("{} clicks is too many", value)),
+    }
+}
+
+fn parse_rotations(input: &str) -> Vec<Rotation> {
+    let mut rotations = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        match parse_rotation(line) {
+            Ok(Some((left, magnitude))) => rotations.push(Rotation {
+                line: i + 1,
+                left,
+                magnitude,
+            }),
+            Ok(None) => {}
+            Err(e) => panic!("Line {}: {}", i + 1, e),
         }
     }
+    rotations
+}
 
+/// How often `dial` stops at 0 after a rotation.
+pub fn count_stops_at_zero(input: &str, mut dial: Dial) -> i64 {
+    let mut zeroes = 0;
+    for rotation in parse_rotations(input) {
+        dial.rotate(rotation.clicks());
+        if dial.position() == 0 {
+            zeroes += 1;
+        }
+    }
     zeroes
 }
 
-pub fn part2(input: &str) -> i32 {
-    let mut offset = 50;
-    let mut zeroes = 0;
+/// How often `dial` points at 0, also in the middle of a rotation.
+pub fn count_zero_passes(input: &str, mut dial: Dial) -> i64 {
+    parse_rotations(input)
+        .iter()
+        .map(|rotation| dial.rotate(rotation.clicks()))
+        .sum()
+}
 
-    for line in input.lines() {
-        let dir = &line[0..1];
-        let mut value: i32 = match line[1..].parse() {
-            Ok(v) => v,
-            Err(_) => panic!("Failed to parse integer from line: {}", &line[1..]),
-        };
+/// What one rotation did to the dial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    pub line: usize,
+    /// `L` or `R`
+    pub direction: char,
+    pub magnitude: i64,
+    pub start: i64,
+    pub end: i64,
+    /// Times the dial pointed at 0, including where it stopped
+    pub zeroes: i64,
+    pub landed_on_zero: bool,
+}
 
-        while value > 0 {
-            if dir == "L" {
-                offset -= 1;
-                value -= 1;
-            } else {
-                offset += 1;
-                value -= 1;
+/// Every rotation of `dial` with where it started and ended.
+pub fn trace(input: &str, mut dial: Dial) -> Vec<TraceStep> {
+    parse_rotations(input)
+        .iter()
+        .map(|rotation| {
+            let start = dial.position();
+            let zeroes = dial.rotate(rotation.clicks());
+            TraceStep {
+                line: rotation.line,
+                direction: if rotation.left { 'L' } else { 'R' },
+                magnitude: rotation.magnitude,
+                start,
+                end: dial.position(),
+                zeroes,
+                landed_on_zero: dial.position() == 0,
             }
+        })
+        .collect()
+}
 
-            offset = offset % 100;
+pub fn trace_csv(steps: &[TraceStep]) -> String {
+    let mut out = String::from("line,direction,magnitude,start,end,zeroes,landed_on_zero\n");
+    for step in steps {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            step.line,
+            step.direction,
+            step.magnitude,
+            step.start,
+            step.end,
+            step.zeroes,
+            step.landed_on_zero
+        ));
+    }
+    out
+}
 
-            if offset == 0 {
-                zeroes += 1;
-            }
-        }
+/// A JSON array with one object per step.
+pub fn trace_json(steps: &[TraceStep]) -> String {
+    let objects: Vec<String> = steps
+        .iter()
+        .map(|step| {
+            format!(
+                "  {{\"line\": {}, \"direction\": \"{}\", \"magnitude\": {}, \"start\": {}, \"end\": {}, \"zeroes\": {}, \"landed_on_zero\": {}}}",
+                step.line,
+                step.direction,
+                step.magnitude,
+                step.start,
+                step.end,
+                step.zeroes,
+                step.landed_on_zero
+            )
+        })
+        .collect();
+    if objects.is_empty() {
+        return "[]\n".to_string();
     }
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
 
-    zeroes
+pub fn part1(input: &str) -> i64 {
+    count_stops_at_zero(input, Dial::default())
+}
+
+pub fn part2(input: &str) -> i64 {
+    count_zero_passes(input, Dial::default())
 }
 
 #[cfg(test)]
@@ -72,6 +234,223 @@ mod tests {
         assert_eq!(6, part2(&input));
     }
 
+    /// Turns the dial one click at a time.
+    fn part2_by_clicks(input: &str) -> i64 {
+        let mut offset = 50;
+        let mut zeroes = 0;
+
+        for line in input.lines() {
+            let dir = &line[0..1];
+            let mut value: i32 = match line[1..].parse() {
+                Ok(v) => v,
+                Err(_) => panic!("Failed to parse integer from line: {}", &line[1..]),
+            };
+
+            while value > 0 {
+                if dir == "L" {
+                    offset -= 1;
+                    value -= 1;
+                } else {
+                    offset += 1;
+                    value -= 1;
+                }
+
+                offset %= 100;
+
+                if offset == 0 {
+                    zeroes += 1;
+                }
+            }
+        }
+
+        zeroes
+    }
+
+    #[test]
+    fn test_part2_matches_clicks() {
+        // Rotations of all sizes, including ones that stop exactly on 0
+        let mut lines = Vec::new();
+        let mut state: u32 = 1;
+        for i in 0..500 {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            let dir = if state >> 16 & 1 == 0 { 'L' } else { 'R' };
+            let value = match i % 4 {
+                0 => (state >> 8) % 10,
+                1 => (state >> 8) % 1000,
+                _ => (state >> 8) % 300,
+            };
+            lines.push(format!("{}{}", dir, value));
+        }
+        lines.extend(["L50", "R100", "L200", "R0", "L0", "R50", "L1"].map(String::from));
+        let input = lines.join("\n");
+        assert_eq!(part2_by_clicks(&input), part2(&input));
+    }
+
+    #[test]
+    fn test_part2_large_rotation() {
+        assert_eq!(10_000_001, part2("R1000000000\nL50"));
+        assert_eq!(1, part2("L50"));
+        assert_eq!(0, part2("L49"));
+        assert_eq!(2, part2("L150"));
+    }
+
+    #[test]
+    fn test_part2_beyond_i32() {
+        let input = "R1000000000\n".repeat(300);
+        assert_eq!(3_000_000_000, part2(&input));
+    }
+
+    #[test]
+    fn test_dial() {
+        let mut dial = Dial::new(10, -3);
+        assert_eq!(7, dial.position());
+        assert_eq!(2, dial.rotate(-17));
+        assert_eq!(0, dial.position());
+        assert_eq!(1, dial.zero_passes(-10));
+        assert_eq!(0, dial.zero_passes(-9));
+        assert_eq!(2, dial.rotate(25));
+        assert_eq!(5, dial.position());
+        assert_eq!(0, dial.rotate(0));
+    }
+
+    #[test]
+    fn test_dial_near_i64_max() {
+        let mut dial = Dial::default();
+        // 92233720368547758 full turns and 7 clicks, from 50 to 57
+        assert_eq!(92_233_720_368_547_758, dial.rotate(i64::MAX));
+        assert_eq!(57, dial.position());
+        // The same turns back, the last 7 clicks do not reach 0 either
+        assert_eq!(92_233_720_368_547_758, dial.rotate(-i64::MAX));
+        assert_eq!(50, dial.position());
+        assert_eq!(
+            92_233_720_368_547_759,
+            part2("L9223372036854775807\nR0\nL50")
+        );
+
+        let mut dial = Dial::new(1, 0);
+        assert_eq!(i64::MAX, dial.rotate(i64::MAX));
+        assert_eq!(0, dial.position());
+    }
+
+    #[test]
+    fn test_dial_size_and_start() {
+        let input = "R3\nL1\nR5";
+        // 4 -> 0 -> 6 -> 4, passing 0 again on the last turn
+        assert_eq!(1, count_stops_at_zero(input, Dial::new(7, 4)));
+        assert_eq!(2, count_zero_passes(input, Dial::new(7, 4)));
+        // 0 -> 3 -> 2 -> 0
+        assert_eq!(1, count_stops_at_zero(input, Dial::new(7, 0)));
+        // 1 -> 0 -> 3 -> 0, passing 0 twice on the last turn
+        assert_eq!(2, count_stops_at_zero(input, Dial::new(4, 1)));
+        assert_eq!(3, count_zero_passes(input, Dial::new(4, 1)));
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(Ok(Some((true, 68))), parse_rotation("L68"));
+        assert_eq!(Ok(Some((false, 5))), parse_rotation("r5"));
+        assert_eq!(Ok(Some((true, 12))), parse_rotation("  l 12  "));
+        assert_eq!(Ok(Some((false, 0))), parse_rotation("R0 # no-op"));
+        assert_eq!(Ok(None), parse_rotation(""));
+        assert_eq!(Ok(None), parse_rotation("   "));
+        assert_eq!(Ok(None), parse_rotation("# L10"));
+
+        assert_eq!(
+            Err("unknown direction 'X', expected L or R".to_string()),
+            parse_rotation("X10")
+        );
+        assert_eq!(
+            Err("expected a number of clicks after L, got \"\"".to_string()),
+            parse_rotation("L")
+        );
+        assert_eq!(
+            Err("expected a number of clicks after R, got \"-5\"".to_string()),
+            parse_rotation("R-5")
+        );
+        assert_eq!(
+            Err("expected a number of clicks after L, got \"1 0\"".to_string()),
+            parse_rotation("L1 0")
+        );
+        assert_eq!(
+            Err("99999999999999999999 clicks is too many".to_string()),
+            parse_rotation("L99999999999999999999")
+        );
+    }
+
+    #[test]
+    fn test_parse_largest_rotation() {
+        assert_eq!(
+            Ok(Some((false, i64::MAX))),
+            parse_rotation("R9223372036854775807")
+        );
+        assert_eq!(
+            Err("9223372036854775808 clicks is too many".to_string()),
+            parse_rotation("R9223372036854775808")
+        );
+        // Accepted lines must not crash the dial later on
+        let input = "L9223372036854775807\nR9223372036854775807";
+        assert_eq!(0, part1(input));
+        assert_eq!(184_467_440_737_095_516, part2(input));
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        let input = "# Start at 50\nL50\n\n  r100 # all the way round\nl 1\n";
+        assert_eq!(2, part1(input));
+        assert_eq!(2, part2(input));
+        let lines: Vec<usize> = trace(input, Dial::default())
+            .iter()
+            .map(|s| s.line)
+            .collect();
+        assert_eq!(vec![2, 4, 5], lines);
+    }
+
+    #[test]
+    #[should_panic(expected = "Line 3: unknown direction 'U', expected L or R")]
+    fn test_unknown_direction() {
+        part1("L1\nR2\nU3\n");
+    }
+
+    #[test]
+    fn test_trace_example() {
+        let input =
+            std::fs::read_to_string("inputs/day01/example1.txt").expect("Example file not found");
+        let steps = trace(&input, Dial::default());
+        assert_eq!(10, steps.len());
+        assert_eq!(
+            TraceStep {
+                line: 1,
+                direction: 'L',
+                magnitude: 68,
+                start: 50,
+                end: 82,
+                zeroes: 1,
+                landed_on_zero: false,
+            },
+            steps[0]
+        );
+        assert_eq!(3, steps.iter().filter(|s| s.landed_on_zero).count());
+        assert_eq!(6, steps.iter().map(|s| s.zeroes).sum::<i64>());
+        assert!(steps.windows(2).all(|w| w[0].end == w[1].start));
+    }
+
+    #[test]
+    fn test_trace_export() {
+        let steps = trace("L68\nL30", Dial::default());
+        assert_eq!(
+            "line,direction,magnitude,start,end,zeroes,landed_on_zero\n\
+             1,L,68,50,82,1,false\n\
+             2,L,30,82,52,0,false\n",
+            trace_csv(&steps)
+        );
+        assert_eq!(
+            "[\n  {\"line\": 1, \"direction\": \"L\", \"magnitude\": 68, \"start\": 50, \"end\": 82, \"zeroes\": 1, \"landed_on_zero\": false},\n  \
+             {\"line\": 2, \"direction\": \"L\", \"magnitude\": 30, \"start\": 82, \"end\": 52, \"zeroes\": 0, \"landed_on_zero\": false}\n]\n",
+            trace_json(&steps)
+        );
+        assert_eq!("[]\n", trace_json(&[]));
+    }
+
     #[test]
     fn test_part1_input() {
         let input = std::fs::read_to_string("inputs/day01/input.txt").expect("Input file not found");
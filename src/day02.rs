@@ -1,79 +1,231 @@
-pub fn part1(input: &str) -> i64 {
-    let mut matching_numbers = Vec::<i64>::new();
-
-    // Input format: "11-22,33-44,55-66,..."
+// Input format: "11-22,33-44,55-66,..."
+fn parse_ranges(input: &str) -> Vec<(i64, i64)> {
+    let mut ranges = Vec::new();
     for substr in input.trim().split(",") {
-        let range_parts: Vec<&str> = substr.split('-').collect();
-        let start: i64 = range_parts[0].trim().parse().unwrap();
-        let end: i64 = range_parts[1].trim().parse().unwrap();
-
-        let mut current = start;
-        while current <= end {
-            let current_str = current.to_string();
-            let length = current_str.len();
-            if length % 2 != 0 {
-                current += 1;
-                continue;
-            }
+        let parsed = substr
+            .split_once('-')
+            .and_then(|(start, end)| Some((start.trim().parse().ok()?, end.trim().parse().ok()?)));
+        let (start, end): (i64, i64) = match parsed {
+            Some(range) => range,
+            None => panic!("Range {:?} should look like START-END", substr.trim()),
+        };
+        // A reversed range is more likely a typo than a wish for the same IDs
+        if start > end {
+            panic!("Range {}-{} is reversed", start, end);
+        }
+        ranges.push((start, end));
+    }
+    merge_ranges(ranges)
+}
 
-            let (left, right) = current_str.split_at(length / 2);
-            if left == right {
-                matching_numbers.push(current);
-            }
+/// Sorts the ranges and merges those that overlap or touch, so every ID is in
+/// at most one range.
+fn merge_ranges(mut ranges: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Which numbers count as a block of digits written several times in a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RepeatRule {
+    pub min_repeats: u32,
+    pub max_repeats: Option<u32>,
+    /// Digits in the repeated block, at least 1
+    pub min_block_length: u32,
+    /// Base the digits are written in
+    pub base: u32,
+}
+
+impl RepeatRule {
+    /// A decimal block written exactly `repeats` times.
+    pub fn exactly(repeats: u32) -> Self {
+        RepeatRule {
+            min_repeats: repeats,
+            max_repeats: Some(repeats),
+            min_block_length: 1,
+            base: 10,
+        }
+    }
 
-            current += 1;
+    /// A decimal block written `repeats` times or more.
+    pub fn at_least(repeats: u32) -> Self {
+        RepeatRule {
+            min_repeats: repeats,
+            max_repeats: None,
+            min_block_length: 1,
+            base: 10,
         }
     }
 
-    matching_numbers.iter().sum()
+    fn allows(&self, block_length: u32, repeats: u32) -> bool {
+        block_length >= self.min_block_length
+            && repeats >= self.min_repeats
+            && self.max_repeats.is_none_or(|max| repeats <= max)
+    }
+
+    fn validate(&self) {
+        if self.base < 2 {
+            panic!("Base must be at least 2, got {}", self.base);
+        }
+        if self.min_repeats == 0 {
+            panic!("A block must be written at least once");
+        }
+    }
 }
 
-pub fn part2(input: &str) -> i64 {
-    let mut matching_numbers = Vec::<i64>::new();
+/// How many numbers match, and their sum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub count: i128,
+    pub sum: i128,
+}
 
-    // Input format: "11-22,33-44,55-66,..."
-    for substr in input.trim().split(",") {
-        let range_parts: Vec<&str> = substr.split('-').collect();
-        let start: i64 = range_parts[0].trim().parse().unwrap();
-        let end: i64 = range_parts[1].trim().parse().unwrap();
-
-        // println!("Processing range {}-{}", start, end);
-
-        let mut current = start;
-        while current <= end {
-            let current_str = current.to_string();
-            let length = current_str.len();
-
-            // println!("Checking number {}", current);
-
-            for group_length in 1..=length / 2 {
-                if length % group_length != 0 && group_length != 1 {
-                    continue;
-                }
-
-                // println!("Checking number {} with group length {}", current, group_length);
-
-                let mut all_match = true;
-                let first_group = &current_str[0..group_length];
-                for i in (group_length..length).step_by(group_length) {
-                    let next_group = &current_str[i..i + group_length];
-                    if first_group != next_group {
-                        all_match = false;
-                        break;
-                    }
-                }
-
-                if all_match {
-                    matching_numbers.push(current);
-                    break;
-                }
-            }
+impl std::ops::Add for Tally {
+    type Output = Tally;
 
-            current += 1;
+    fn add(self, other: Tally) -> Tally {
+        Tally {
+            count: self.count + other.count,
+            sum: self.sum + other.sum,
         }
     }
+}
+
+impl std::ops::Sub for Tally {
+    type Output = Tally;
+
+    fn sub(self, other: Tally) -> Tally {
+        Tally {
+            count: self.count - other.count,
+            sum: self.sum - other.sum,
+        }
+    }
+}
+
+fn digit_count(n: i64, base: u32) -> u32 {
+    n.max(1).ilog(base as i64) + 1
+}
+
+/// The numbers in `start..=end` that are a `block_length` digit block
+/// (without leading zero) written `repeats` times.
+///
+/// Such numbers are `block * multiplier` with e.g. multiplier 1001001 for a
+/// 3-digit block repeated 3 times, so they form an arithmetic sequence.
+fn repeated(start: i64, end: i64, base: u32, block_length: u32, repeats: u32) -> Tally {
+    let base = base as i128;
+    let multiplier: i128 = (0..repeats).map(|i| base.pow(block_length * i)).sum();
+    let smallest_block = base.pow(block_length - 1);
+    let largest_block = base.pow(block_length) - 1;
+
+    let low = smallest_block.max((start as i128 + multiplier - 1) / multiplier);
+    let high = largest_block.min(end as i128 / multiplier);
+    if low > high {
+        return Tally::default();
+    }
+    Tally {
+        count: high - low + 1,
+        sum: multiplier * (low + high) * (high - low + 1) / 2,
+    }
+}
+
+/// The numbers with `length` digits in `start..=end` whose shortest repeating
+/// block has `block_length` digits.
+fn primitive(start: i64, end: i64, base: u32, length: u32, block_length: u32) -> Tally {
+    // Repeating a shorter block that fits `block_length` also repeats
+    // `block_length`, so take those out
+    let mut tally = repeated(start, end, base, block_length, length / block_length);
+    for shorter in (1..block_length).filter(|&d| block_length.is_multiple_of(d)) {
+        tally = tally - primitive(start, end, base, length, shorter);
+    }
+    tally
+}
+
+/// Whether `id`, written in `rule.base` without leading zeroes, is one block
+/// of digits repeated as `rule` allows. This is the definition the counting
+/// below has to agree with.
+pub fn is_repeated(id: i64, rule: &RepeatRule) -> bool {
+    rule.validate();
+    if id < 1 {
+        return false;
+    }
+
+    let mut digits = Vec::new();
+    let mut rest = id;
+    while rest > 0 {
+        digits.push(rest % rule.base as i64);
+        rest /= rule.base as i64;
+    }
+    digits.reverse();
+
+    // Only blocks whose length divides the number's length can tile it
+    let length = digits.len();
+    (1..=length)
+        .filter(|&block_length| length.is_multiple_of(block_length))
+        .any(|block_length| {
+            rule.allows(block_length as u32, (length / block_length) as u32)
+                && digits
+                    .chunks(block_length)
+                    .all(|block| block == &digits[..block_length])
+        })
+}
+
+/// Counts and sums the numbers in `start..=end` that match `rule`, each once
+/// even if it can be split into blocks in several ways. The work depends on
+/// the number of digits, not on the width of the range.
+pub fn tally_matching(start: i64, end: i64, rule: &RepeatRule) -> Tally {
+    rule.validate();
+    let mut tally = Tally::default();
+    if start > end || end < 1 {
+        return tally;
+    }
+
+    for length in digit_count(start, rule.base)..=digit_count(end, rule.base) {
+        let divisors: Vec<u32> = (1..=length).filter(|&d| length.is_multiple_of(d)).collect();
+        for &shortest in &divisors {
+            // A number made of a `shortest` digit block can also be split into
+            // any block length that is a multiple of it
+            let matches = divisors
+                .iter()
+                .filter(|&&d| d.is_multiple_of(shortest))
+                .any(|&d| rule.allows(d, length / d));
+            if matches {
+                tally = tally + primitive(start, end, rule.base, length, shortest);
+            }
+        }
+    }
+    tally
+}
 
-    matching_numbers.iter().sum()
+pub fn sum_matching(start: i64, end: i64, rule: &RepeatRule) -> i128 {
+    tally_matching(start, end, rule).sum
+}
+
+pub fn count_matching(start: i64, end: i64, rule: &RepeatRule) -> i128 {
+    tally_matching(start, end, rule).count
+}
+
+/// Sum of the IDs matching `rule` in any of the ranges. An ID in several
+/// ranges counts once.
+pub fn sum_invalid_ids(input: &str, rule: &RepeatRule) -> i128 {
+    parse_ranges(input)
+        .iter()
+        .map(|&(start, end)| sum_matching(start, end, rule))
+        .sum()
+}
+
+pub fn part1(input: &str) -> i128 {
+    sum_invalid_ids(input, &RepeatRule::exactly(2))
+}
+
+pub fn part2(input: &str) -> i128 {
+    sum_invalid_ids(input, &RepeatRule::at_least(2))
 }
 
 #[cfg(test)]
@@ -92,6 +244,216 @@ mod tests {
         assert_eq!(4174379265, part2(&input));
     }
 
+    #[test]
+    fn test_is_repeated() {
+        // (id, exactly twice as in part 1, at least twice as in part 2)
+        let cases = [
+            (0, false, false),
+            (7, false, false),
+            (11, true, true),
+            (111, false, true),
+            (1111, true, true),
+            (1010, true, true),
+            (1212121, false, false),
+            (121212, false, true),
+            (123123, true, true),
+            (123123123, false, true),
+            (123123124, false, false),
+            (10101, false, false),
+            (1001, false, false),
+            (446446, true, true),
+            (2121212121, false, true),
+        ];
+        for (id, twice, at_least_twice) in cases {
+            assert_eq!(twice, is_repeated(id, &RepeatRule::exactly(2)), "{}", id);
+            assert_eq!(
+                at_least_twice,
+                is_repeated(id, &RepeatRule::at_least(2)),
+                "{}",
+                id
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_repeated_rules() {
+        let long_blocks = RepeatRule {
+            min_block_length: 2,
+            ..RepeatRule::at_least(2)
+        };
+        assert!(!is_repeated(1111, &RepeatRule::exactly(3)));
+        assert!(is_repeated(1111, &long_blocks));
+        assert!(!is_repeated(111, &long_blocks));
+        // 0xABAB
+        let hex = RepeatRule {
+            base: 16,
+            ..RepeatRule::exactly(2)
+        };
+        assert!(is_repeated(0xabab, &hex));
+        assert!(!is_repeated(0xabab, &RepeatRule::exactly(2)));
+    }
+
+    /// Checks every number in the ranges.
+    fn sum_by_scanning(input: &str, rule: &RepeatRule) -> i128 {
+        parse_ranges(input)
+            .iter()
+            .map(|&(start, end)| tally_by_scanning(start, end, rule).sum)
+            .sum()
+    }
+
+    #[test]
+    fn test_matches_scanning() {
+        let input =
+            std::fs::read_to_string("inputs/day02/example1.txt").expect("Example file not found");
+        assert_eq!(
+            sum_by_scanning(&input, &RepeatRule::exactly(2)),
+            part1(&input)
+        );
+        assert_eq!(
+            sum_by_scanning(&input, &RepeatRule::at_least(2)),
+            part2(&input)
+        );
+
+        // Ranges crossing lengths, and ranges that are all repeats
+        let input = "1-7,11-11,20-900,998-1012,123123-123123,200000-1000000";
+        assert_eq!(
+            sum_by_scanning(input, &RepeatRule::exactly(2)),
+            part1(input)
+        );
+        assert_eq!(
+            sum_by_scanning(input, &RepeatRule::at_least(2)),
+            part2(input)
+        );
+    }
+
+    #[test]
+    fn test_wide_range() {
+        // Every 2-digit repeat is 11 * block with block 1..=9, and 4-digit
+        // repeats are 101 * block with block 10..=99
+        assert_eq!(11 * 45 + 101 * 4905, part1("1-9999"));
+        // Scanning all 10-digit numbers would take minutes
+        assert_eq!(
+            100001 * (10000 + 99999) * 90000 / 2,
+            part1("1000000000-9999999999")
+        );
+    }
+
+    /// Checks every number in the range.
+    fn tally_by_scanning(start: i64, end: i64, rule: &RepeatRule) -> Tally {
+        let mut tally = Tally::default();
+        for n in start.max(1)..=end {
+            if is_repeated(n, rule) {
+                tally = tally
+                    + Tally {
+                        count: 1,
+                        sum: n as i128,
+                    };
+            }
+        }
+        tally
+    }
+
+    #[test]
+    fn test_rules_match_scanning() {
+        let rules = [
+            RepeatRule::exactly(2),
+            RepeatRule::exactly(3),
+            RepeatRule::at_least(2),
+            RepeatRule::at_least(1),
+            RepeatRule {
+                min_repeats: 2,
+                max_repeats: Some(4),
+                min_block_length: 2,
+                base: 10,
+            },
+            RepeatRule {
+                min_repeats: 2,
+                max_repeats: None,
+                min_block_length: 1,
+                base: 2,
+            },
+            RepeatRule {
+                min_repeats: 3,
+                max_repeats: None,
+                min_block_length: 1,
+                base: 16,
+            },
+        ];
+        for rule in &rules {
+            for (start, end) in [
+                (1, 100_000),
+                (95, 115),
+                (4000, 20000),
+                (1_000_000, 1_050_000),
+            ] {
+                assert_eq!(
+                    tally_by_scanning(start, end, rule),
+                    tally_matching(start, end, rule),
+                    "{:?} in {}-{}",
+                    rule,
+                    start,
+                    end
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rule_examples() {
+        // 1111 is 1 four times and 11 twice, but only counted once
+        assert_eq!(1, count_matching(1111, 1111, &RepeatRule::at_least(2)));
+        assert_eq!(1, count_matching(1111, 1111, &RepeatRule::exactly(4)));
+        assert_eq!(0, count_matching(1111, 1111, &RepeatRule::exactly(3)));
+        // In binary 7 = 111 and 10 = 1010 are repeats, 5 = 101 is not
+        let binary = RepeatRule {
+            base: 2,
+            ..RepeatRule::at_least(2)
+        };
+        assert_eq!(7 + 10, sum_matching(4, 10, &binary));
+        assert_eq!(0, count_matching(20, 10, &RepeatRule::exactly(2)));
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        assert_eq!(vec![(11, 30)], merge_ranges(vec![(15, 30), (11, 22)]));
+        assert_eq!(vec![(1, 9)], merge_ranges(vec![(5, 9), (1, 4)]));
+        assert_eq!(
+            vec![(1, 3), (5, 9)],
+            merge_ranges(vec![(5, 9), (1, 3), (6, 7)])
+        );
+        assert_eq!(
+            vec![(i64::MAX - 1, i64::MAX)],
+            merge_ranges(vec![(i64::MAX - 1, i64::MAX), (i64::MAX, i64::MAX)])
+        );
+    }
+
+    #[test]
+    fn test_overlapping_ranges_count_once() {
+        // 11 and 22 are in both ranges
+        assert_eq!(11 + 22 + 33, part1("11-22,15-33,11-11"));
+        assert_eq!(part2("11-33"), part2("11-22,15-30,22-33"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Range 30-11 is reversed")]
+    fn test_reversed_range() {
+        part1("11-22,30-11");
+    }
+
+    #[test]
+    #[should_panic(expected = "Range \"11-\" should look like START-END")]
+    fn test_invalid_range() {
+        part1("11-");
+    }
+
+    #[test]
+    fn test_sum_beyond_i64() {
+        // 18-digit repeats up to i64::MAX add up to far more than i64::MAX
+        let total = part1("100000000100000000-999999999999999999");
+        assert!(total > i64::MAX as i128);
+        assert_eq!(1000000001 * (100000000 + 999999999) * 900000000 / 2, total);
+    }
+
     #[test]
     fn test_part1_input() {
         let input = std::fs::read_to_string("inputs/day02/input.txt").expect("Input file not found");
@@ -1,79 +1,70 @@
-struct Digit {
-    value: u8,
-    offset: i32,
-}
-
-fn joltage(line: &str, digits: u8) -> i64 {
-    let mut total: i64 = 0;
-    let mut start_offset: usize = 0;
-    for digit in 0..digits {
-        total *= 10;
-        let max_digit_i: Digit = max_digit(line, start_offset, digits - digit);
-        total += max_digit_i.value as i64;
-        start_offset = max_digit_i.offset as usize + 1;
-    }
+use crate::bignum::BigUint;
 
-    total
+/// The digits picked from a bank and the number they make.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub value: BigUint,
+    /// Positions of the picked digits in the bank, ascending
+    pub indices: Vec<usize>,
 }
 
-fn max_digit(line: &str, start_offset: usize, digits: u8) -> Digit {
-    // Go through the rest to find the best second digit
-    let mut max_digit: Digit = Digit {
-        value: 0,
-        offset: -1,
-    };
-    for (offset, c) in line
+/// Picks `digits` batteries from the bank, keeping their order, to make the
+/// largest number.
+///
+/// A digit is dropped in favour of a larger one after it as long as enough
+/// digits are left to fill the selection, so the kept digits form a stack
+/// that only decreases. One pass over the bank.
+pub fn select(line: &str, digits: usize) -> Selection {
+    let bank: Vec<u8> = line
         .chars()
-        .enumerate()
-        .take(line.len() - (digits as usize) + 1)
-        .skip(start_offset as usize)
-    {
-        let digit: u8 = match c.to_digit(10) {
+        .map(|c| match c.to_digit(10) {
             Some(d) => d as u8,
             None => panic!("Invalid character {c} in input"),
-        };
+        })
+        .collect();
+    if digits > bank.len() {
+        panic!(
+            "Cannot pick {} digits from a bank of {}",
+            digits,
+            bank.len()
+        );
+    }
 
-        match digit {
-            // We can't find a better value
-            9 => {
-                max_digit = Digit {
-                    value: 9,
-                    offset: offset as i32,
-                };
-                break;
-            }
-            _ => {
-                if digit > max_digit.value {
-                    max_digit = Digit {
-                        value: digit,
-                        offset: offset as i32,
-                    };
-                }
-            }
+    let mut droppable = bank.len() - digits;
+    let mut stack: Vec<usize> = Vec::with_capacity(bank.len());
+    for (i, &digit) in bank.iter().enumerate() {
+        // Equal digits stay, so the earliest of them is picked
+        while droppable > 0 && stack.last().is_some_and(|&top| bank[top] < digit) {
+            stack.pop();
+            droppable -= 1;
         }
+        stack.push(i);
     }
+    stack.truncate(digits);
 
-    if max_digit.offset == -1 {
-        panic!("No valid digits found in line after offset {start_offset}");
+    let picked: Vec<u8> = stack.iter().map(|&i| bank[i]).collect();
+    Selection {
+        value: BigUint::from_digits(&picked),
+        indices: stack,
     }
-
-    max_digit
 }
 
-pub fn part1(input: &str) -> i64 {
-    let mut total: i64 = 0;
+/// Sum of the largest `digits`-digit joltage of every bank. Exact for any
+/// number of digits.
+pub fn total_joltage(input: &str, digits: usize) -> BigUint {
+    let mut total = BigUint::zero();
     for line in input.lines() {
-        total += joltage(line, 2);
+        total += &select(line, digits).value;
     }
     total
 }
 
-pub fn part2(input: &str) -> i64 {
-    let mut total: i64 = 0;
-    for line in input.lines() {
-        total += joltage(line, 12);
-    }
-    total
+pub fn part1(input: &str) -> BigUint {
+    total_joltage(input, 2)
+}
+
+pub fn part2(input: &str) -> BigUint {
+    total_joltage(input, 12)
 }
 
 #[cfg(test)]
@@ -83,13 +74,150 @@ mod tests {
     #[test]
     fn test_part1_example() {
         let input = std::fs::read_to_string("inputs/day03/example1.txt").expect("Example file not found");
-        assert_eq!(357, part1(&input));
+        assert_eq!(BigUint::from(357), part1(&input));
     }
 
     #[test]
     fn test_part2_example() {
         let input = std::fs::read_to_string("inputs/day03/example1.txt").expect("Example file not found");
-        assert_eq!(3121910778619, part2(&input));
+        assert_eq!(BigUint::from(3121910778619), part2(&input));
+    }
+
+    struct Digit {
+        value: u8,
+        offset: i32,
+    }
+
+    /// Picks one digit at a time, scanning the rest of the bank each time.
+    fn joltage(line: &str, digits: u8) -> i64 {
+        let mut total: i64 = 0;
+        let mut start_offset: usize = 0;
+        for digit in 0..digits {
+            total *= 10;
+            let max_digit_i: Digit = max_digit(line, start_offset, digits - digit);
+            total += max_digit_i.value as i64;
+            start_offset = max_digit_i.offset as usize + 1;
+        }
+
+        total
+    }
+
+    fn max_digit(line: &str, start_offset: usize, digits: u8) -> Digit {
+        // Go through the rest to find the best second digit
+        let mut max_digit: Digit = Digit {
+            value: 0,
+            offset: -1,
+        };
+        for (offset, c) in line
+            .chars()
+            .enumerate()
+            .take(line.len() - (digits as usize) + 1)
+            .skip(start_offset)
+        {
+            let digit: u8 = match c.to_digit(10) {
+                Some(d) => d as u8,
+                None => panic!("Invalid character {c} in input"),
+            };
+
+            match digit {
+                // We can't find a better value
+                9 => {
+                    max_digit = Digit {
+                        value: 9,
+                        offset: offset as i32,
+                    };
+                    break;
+                }
+                _ => {
+                    if digit > max_digit.value {
+                        max_digit = Digit {
+                            value: digit,
+                            offset: offset as i32,
+                        };
+                    }
+                }
+            }
+        }
+
+        if max_digit.offset == -1 {
+            panic!("No valid digits found in line after offset {start_offset}");
+        }
+
+        max_digit
+    }
+
+    #[test]
+    fn test_select_example() {
+        let selection = select("818181911112111", 12);
+        assert_eq!(BigUint::from(888911112111), selection.value);
+        assert_eq!(
+            vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+            selection.indices
+        );
+
+        let selection = select("234234234234278", 2);
+        assert_eq!(BigUint::from(78), selection.value);
+        assert_eq!(vec![13, 14], selection.indices);
+    }
+
+    #[test]
+    fn test_select_edge_cases() {
+        assert!(select("123", 0).value.is_zero());
+        assert_eq!(BigUint::from(123), select("123", 3).value);
+        // The first of equal digits is kept
+        assert_eq!(vec![0, 1], select("9999", 2).indices);
+        assert_eq!(vec![0, 3], select("5115", 2).indices);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot pick 4 digits from a bank of 3")]
+    fn test_select_too_many() {
+        select("123", 4);
+    }
+
+    #[test]
+    fn test_select_matches_oracle() {
+        let mut state: u64 = 3;
+        for length in 1..40 {
+            let line: String = (0..length)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    // Few distinct digits give many ties
+                    char::from(b'1' + (state >> 60) as u8 % 4)
+                })
+                .collect();
+            for digits in 1..=length.min(18) {
+                let selection = select(&line, digits);
+                assert_eq!(
+                    Some(joltage(&line, digits as u8) as u128),
+                    selection.value.to_u128(),
+                    "{} {}",
+                    line,
+                    digits
+                );
+                let picked: String = selection.indices.iter().map(|&i| &line[i..i + 1]).collect();
+                assert_eq!(selection.value.to_string(), picked);
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_selections() {
+        // Digits 1 to 9 and then 0, ten times over
+        let bank = "1234567890".repeat(10);
+        assert_eq!("99999999990", select(&bank, 11).value.to_string());
+        assert_eq!("99999999901234567890", select(&bank, 20).value.to_string());
+        let selection = select(&bank, 100);
+        assert_eq!(bank, selection.value.to_string());
+        assert_eq!(None, selection.value.to_u128());
+
+        let input = format!("{}\n{}\n", "9".repeat(60), "9".repeat(60));
+        assert_eq!(
+            format!("1{}8", "9".repeat(49)),
+            total_joltage(&input, 50).to_string()
+        );
     }
 
     #[test]
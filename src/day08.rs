@@ -1,19 +1,8 @@
 use std::collections::HashSet;
 
-struct Node {
-    pub x: i64,
-    pub y: i64,
-    pub z: i64,
-}
-
-fn distance(first: &Node, other: &Node) -> i64 {
-    let dx = (first.x - other.x).pow(2);
-    let dy = (first.y - other.y).pow(2);
-    let dz = (first.z - other.z).pow(2);
-    (dx + dy + dz).isqrt()
-}
+use crate::space::{Point3, closest_pairs};
 
-fn parse_nodes(input: &str) -> Vec<Node> {
+fn parse_nodes(input: &str) -> Vec<Point3> {
     input
         .lines()
         .map(|line| {
@@ -22,29 +11,11 @@ fn parse_nodes(input: &str) -> Vec<Node> {
                 .split(',')
                 .filter_map(|part| part.parse::<i64>().ok())
                 .collect();
-            Node {
-                x: parts[0],
-                y: parts[1],
-                z: parts[2],
-            }
+            Point3::new(parts[0], parts[1], parts[2])
         })
         .collect()
 }
 
-fn shortest_n_distances(nodes: &Vec<Node>, n: usize) -> Vec<(usize, usize)> {
-    let mut pairs: Vec<(i64, usize, usize)> = Vec::new();
-
-    for (i, node) in nodes.iter().enumerate() {
-        for (j, other) in nodes.iter().enumerate().skip(i + 1) {
-            let dist = distance(node, other);
-            pairs.push((dist, i, j));
-        }
-    }
-
-    pairs.sort_unstable_by_key(|&(dist, _, _)| dist);
-    pairs.into_iter().take(n).map(|(_, i, j)| (i, j)).collect()
-}
-
 fn create_initial_circuits(n: usize) -> Vec<HashSet<usize>> {
     let mut circuits: Vec<HashSet<usize>> = Vec::new();
     for i in 0..n {
@@ -92,7 +63,7 @@ fn connect_n_nodes(shortest: Vec<(usize, usize)>, n: usize) -> Vec<HashSet<usize
 
 pub fn part1(input: &str, n: usize) -> i32 {
     let nodes = parse_nodes(input);
-    let shortest = shortest_n_distances(&nodes, n);
+    let shortest: Vec<(usize, usize)> = closest_pairs(&nodes).take(n).collect();
     let mut circuits = connect_n_nodes(shortest, nodes.len());
     circuits.sort_by_key(|c| -(c.len() as i32));
     circuits.iter().take(3).map(|c| c.len() as i32).product()
@@ -100,24 +71,22 @@ pub fn part1(input: &str, n: usize) -> i32 {
 
 pub fn part2(input: &str) -> i64 {
     let nodes = parse_nodes(input);
-    let n = nodes.len() * nodes.len() / 2 - nodes.len();
-    let connections = shortest_n_distances(&nodes, n);
     let mut circuits = create_initial_circuits(nodes.len());
 
     let mut final_connection: (usize, usize) = (0, 0);
-    for conn in connections.iter() {
-        connect_and_merge_circuits(&mut circuits, *conn);
+    for conn in closest_pairs(&nodes) {
+        connect_and_merge_circuits(&mut circuits, conn);
 
         let are_done = circuits.len() == 1;
         if are_done {
-            final_connection = *conn;
+            final_connection = conn;
             break;
         }
     }
 
     let node1 = &nodes[final_connection.0];
     let node2 = &nodes[final_connection.1];
-    (node1.x * node2.x) as i64
+    node1.x * node2.x
 }
 
 #[cfg(test)]
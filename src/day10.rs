@@ -1,167 +1,439 @@
-use core::panic;
+use std::fmt;
+use std::time::{Duration, Instant};
 
-use z3::Optimize;
-use z3::ast;
+use crate::gf2::{self, BitMatrix, BitVec};
+use crate::ilp;
+use crate::parallel;
+use crate::solver::SolveError;
 
+#[derive(Debug)]
 struct Config {
     lights_on: Vec<bool>,
     toggles_indices: Vec<Vec<usize>>,
     joltages: Vec<i32>,
 }
 
-fn parse_line(line: &str) -> Config {
-    let parts: Vec<&str> = line.split(' ').collect();
+/// Describes what is wrong with the token starting at byte `column` (1-based).
+fn token_error(column: usize, token: &str, message: String) -> String {
+    format!("Column {} {:?}: {}", column, token, message)
+}
 
-    let lights_on_str = parts[0].trim_matches(&['[', ']'][..]).chars();
-    let lights_on: Vec<bool> = lights_on_str.map(|c| c == '#').collect();
+/// The comma separated numbers inside `open` and `close`.
+fn parse_list<T: std::str::FromStr>(
+    column: usize,
+    token: &str,
+    open: char,
+    close: char,
+) -> Result<Vec<T>, String> {
+    let inner = token
+        .strip_prefix(open)
+        .and_then(|t| t.strip_suffix(close))
+        .ok_or_else(|| token_error(column, token, format!("expected {}...{}", open, close)))?;
+    if inner.is_empty() {
+        return Err(token_error(column, token, "empty list".to_string()));
+    }
+    inner
+        .split(',')
+        .map(|s| {
+            s.parse()
+                .map_err(|_| token_error(column, token, format!("{:?} is not a number", s)))
+        })
+        .collect()
+}
 
-    let mut toggles_indices = Vec::new();
-    let toggles_parts = &parts[1..parts.len() - 1];
-    for part in toggles_parts {
-        let indices_str = part.trim_matches(&['(', ')'][..]);
-        let indices: Vec<usize> = indices_str
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-        toggles_indices.push(indices);
+fn parse_line(line: &str) -> Result<Config, String> {
+    // Tokens with the column they start at
+    let mut parts = Vec::new();
+    let mut column = 1;
+    for part in line.split(' ') {
+        if !part.is_empty() {
+            parts.push((column, part));
+        }
+        column += part.len() + 1;
     }
 
-    let joltages: Vec<i32> = parts[parts.len() - 1]
-        .trim_matches(&['{', '}'][..])
-        .split(',')
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect();
+    let Some(&(column, lights_part)) = parts.first() else {
+        return Err("Empty line".to_string());
+    };
+    let lights_on_str = lights_part
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or_else(|| token_error(column, lights_part, "expected lights in [...]".to_string()))?;
+    if lights_on_str.is_empty() {
+        return Err(token_error(column, lights_part, "no lights".to_string()));
+    }
+    if let Some(c) = lights_on_str.chars().find(|&c| c != '.' && c != '#') {
+        return Err(token_error(
+            column,
+            lights_part,
+            format!("unexpected light {:?}", c),
+        ));
+    }
+    let lights_on: Vec<bool> = lights_on_str.chars().map(|c| c == '#').collect();
+
+    let (column, joltages_part) = match parts.last() {
+        Some(&(column, part)) if parts.len() > 1 && part.starts_with('{') => (column, part),
+        _ => return Err("Missing joltages {...} at the end of the line".to_string()),
+    };
+    let joltages: Vec<i32> = parse_list(column, joltages_part, '{', '}')?;
+    if joltages.len() != lights_on.len() {
+        return Err(token_error(
+            column,
+            joltages_part,
+            format!("{} joltages for {} lights", joltages.len(), lights_on.len()),
+        ));
+    }
+    if let Some(joltage) = joltages.iter().find(|&&j| j < 0) {
+        return Err(token_error(
+            column,
+            joltages_part,
+            format!("negative joltage {}", joltage),
+        ));
+    }
 
-    if lights_on.len() != joltages.len() {
-        panic!("Number of lights does not match number of joltages");
+    let toggles_parts = &parts[1..parts.len() - 1];
+    if toggles_parts.is_empty() {
+        return Err("Missing buttons (...) between the lights and the joltages".to_string());
+    }
+    let mut toggles_indices = Vec::new();
+    for &(column, part) in toggles_parts {
+        let indices: Vec<usize> = parse_list(column, part, '(', ')')?;
+        for (i, &index) in indices.iter().enumerate() {
+            if index >= lights_on.len() {
+                return Err(token_error(
+                    column,
+                    part,
+                    format!(
+                        "light {} does not exist, there are {}",
+                        index,
+                        lights_on.len()
+                    ),
+                ));
+            }
+            if indices[..i].contains(&index) {
+                return Err(token_error(
+                    column,
+                    part,
+                    format!("light {} listed twice", index),
+                ));
+            }
+        }
+        toggles_indices.push(indices);
     }
 
-    Config {
+    Ok(Config {
         lights_on,
         toggles_indices,
         joltages,
-    }
+    })
 }
 
 fn parse_input(input: &str) -> Vec<Config> {
     let mut configs = Vec::new();
 
-    for line in input.lines() {
-        configs.push(parse_line(line));
+    for (i, line) in input.lines().enumerate() {
+        match parse_line(line) {
+            Ok(config) => configs.push(config),
+            Err(e) => panic!("Line {}: {}", i + 1, e),
+        }
     }
     configs
 }
 
-fn solve_subproblem(config: Config) -> i32 {
-    let optimizer = Optimize::new();
-    let mut light_togglers: Vec<Vec<&ast::Int>> = vec![Vec::new(); config.lights_on.len()];
-
-    let mut buttons_pressed: Vec<ast::Int> = Vec::new();
-    for i in 0..config.toggles_indices.len() {
-        let button_press_count = ast::Int::new_const(format!("button_{}", i));
-        optimizer.assert(&button_press_count.ge(0));
-        buttons_pressed.push(button_press_count);
+/// Which buttons toggle each light, one row per light and a column per button.
+fn toggle_matrix(config: &Config) -> BitMatrix {
+    let mut matrix = BitMatrix::zeros(config.lights_on.len(), config.toggles_indices.len());
+    for (button, button_indices) in config.toggles_indices.iter().enumerate() {
+        for &light_index in button_indices {
+            matrix.set(light_index, button, true);
+        }
     }
+    matrix
+}
 
-    // Map which buttons toggle which lights
-    for (button_press_count, button_indices) in
-        buttons_pressed.iter().zip(config.toggles_indices.iter())
-    {
-        for &light_index in button_indices {
-            light_togglers[light_index].push(&button_press_count);
+/// How often to press each button, and the state the machine ends up in.
+#[derive(Clone, Debug, PartialEq)]
+struct PressPlan {
+    presses: Vec<i64>,
+    lights_on: Vec<bool>,
+    joltages: Vec<i64>,
+}
+
+impl PressPlan {
+    /// Presses the buttons on a machine that starts with all lights off and
+    /// all joltages at 0.
+    fn replay(config: &Config, presses: Vec<i64>) -> Self {
+        if presses.len() != config.toggles_indices.len() {
+            panic!(
+                "Plan has {} buttons but the machine has {}",
+                presses.len(),
+                config.toggles_indices.len()
+            );
+        }
+        let mut lights_on = vec![false; config.lights_on.len()];
+        let mut joltages = vec![0; config.joltages.len()];
+        for (&count, button_indices) in presses.iter().zip(&config.toggles_indices) {
+            for &index in button_indices {
+                lights_on[index] ^= count % 2 == 1;
+                joltages[index] += count;
+            }
+        }
+        PressPlan {
+            presses,
+            lights_on,
+            joltages,
         }
     }
 
-    // For each light, assert its final state
-    for (i, &light_on) in config.lights_on.iter().enumerate() {
-        let light_sum = ast::Int::new_const(format!("light_sum_{}", i));
-        optimizer.assert(&light_sum.eq(&ast::Int::add(&light_togglers[i])));
-        optimizer.assert(&light_sum.modulo(2).eq(if light_on { 1 } else { 0 }));
+    fn total(&self) -> i64 {
+        self.presses.iter().sum()
     }
 
-    optimizer.minimize(&ast::Int::add(&buttons_pressed));
+    /// Whether the recorded state is right and has the lights the machine wants.
+    fn reaches_lights(&self, config: &Config) -> bool {
+        *self == PressPlan::replay(config, self.presses.clone())
+            && self.lights_on == config.lights_on
+    }
 
-    if optimizer.check(&[]) != z3::SatResult::Sat {
-        panic!("No solution found");
+    /// Whether the recorded state is right and has the joltages the machine wants.
+    fn reaches_joltages(&self, config: &Config) -> bool {
+        let joltages: Vec<i64> = config.joltages.iter().map(|&j| j as i64).collect();
+        *self == PressPlan::replay(config, self.presses.clone()) && self.joltages == joltages
     }
+}
 
-    let model = optimizer.get_model().unwrap();
-    let total_presses: i64 = buttons_pressed
-        .iter()
-        .map(|button| model.eval(button, true).unwrap().as_i64().unwrap())
-        .sum();
+/// Why a machine has no answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Failure {
+    /// No way of pressing the buttons reaches the target
+    Infeasible(String),
+    /// The solver gave up
+    Unknown(String),
+}
 
-    total_presses as i32
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Infeasible(reason) => write!(f, "infeasible: {}", reason),
+            Failure::Unknown(reason) => write!(f, "unknown: {}", reason),
+        }
+    }
 }
 
-fn solve_subproblem2(config: Config) -> i32 {
-    let optimizer = Optimize::new();
-    let mut joltage_incrementers: Vec<Vec<&ast::Int>> = vec![Vec::new(); config.joltages.len()];
+/// Total presses over the machines that were solved, and the input lines of
+/// those that were not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartResult {
+    pub total: i64,
+    pub failures: Vec<(usize, Failure)>,
+    /// Time spent on each machine, in input order
+    pub timings: Vec<Duration>,
+}
 
-    let mut buttons_pressed: Vec<ast::Int> = Vec::new();
-    for i in 0..config.toggles_indices.len() {
-        let button_press_count = ast::Int::new_const(format!("button_{}", i));
-        optimizer.assert(&button_press_count.ge(0));
-        buttons_pressed.push(button_press_count);
+impl fmt::Display for PartResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.total)?;
+        for (line, failure) in &self.failures {
+            write!(f, "\nLine {}: {}", line, failure)?;
+        }
+        Ok(())
     }
+}
 
-    // Map which buttons toggle which lights
-    for (button_press_count, button_indices) in
-        buttons_pressed.iter().zip(config.toggles_indices.iter())
-    {
-        for &joltage_index in button_indices {
-            joltage_incrementers[joltage_index].push(&button_press_count);
+/// Nullity above which trying every combination of the null space takes too
+/// long. 2^22 combinations take well under a second.
+const MAX_NULLITY: usize = 22;
+
+fn solve_subproblem(config: &Config) -> Result<PressPlan, Failure> {
+    for (light, &on) in config.lights_on.iter().enumerate() {
+        if on && !config.toggles_indices.iter().any(|b| b.contains(&light)) {
+            return Err(Failure::Infeasible(format!(
+                "Light {} is on but no button toggles it",
+                light
+            )));
         }
     }
 
-    // For each joltage, check the sum
-    for (i, &joltage) in config.joltages.iter().enumerate() {
-        let joltage_sum = ast::Int::new_const(format!("joltage_sum_{}", i));
-        optimizer.assert(&joltage_sum.eq(&ast::Int::add(&joltage_incrementers[i])));
-        optimizer.assert(&joltage_sum.eq(joltage as i64));
+    // Pressing a button twice cancels out, so this is a linear system over GF(2)
+    let matrix = toggle_matrix(config);
+    let target = BitVec::from_bools(&config.lights_on);
+    let Some((presses, basis)) = matrix.solve(&target) else {
+        // Some set of lights is always toggled an even number of times in
+        // total, by any button, but an odd number of them must end up on
+        let lights = matrix
+            .transpose()
+            .null_space()
+            .into_iter()
+            .find(|lights| lights.dot(&target))
+            .expect("An unsolvable system has a parity certificate");
+        return Err(Failure::Infeasible(format!(
+            "Every button toggles an even number of lights {:?}, but an odd number of them are on",
+            lights.ones().collect::<Vec<_>>()
+        )));
+    };
+
+    // Every solution is one combination of the null space away
+    if basis.len() > MAX_NULLITY {
+        return Err(Failure::Unknown(format!(
+            "2^{} ways to combine the buttons are too many to search",
+            basis.len()
+        )));
     }
+    let presses = gf2::min_weight_in_coset(presses, &basis);
+    let presses = presses.to_bools().iter().map(|&p| p as i64).collect();
+    let plan = PressPlan::replay(config, presses);
+    assert!(plan.reaches_lights(config), "Wrong plan {:?}", plan);
+    Ok(plan)
+}
 
-    optimizer.minimize(&ast::Int::add(&buttons_pressed));
+/// How much each button adds to each joltage, one row per joltage.
+fn joltage_matrix(config: &Config) -> Vec<Vec<i64>> {
+    let mut matrix = vec![vec![0; config.toggles_indices.len()]; config.joltages.len()];
+    for (button, button_indices) in config.toggles_indices.iter().enumerate() {
+        for &joltage_index in button_indices {
+            matrix[joltage_index][button] += 1;
+        }
+    }
+    matrix
+}
 
-    if optimizer.check(&[]) != z3::SatResult::Sat {
-        panic!("No solution found");
+fn solve_subproblem2(config: &Config) -> Result<PressPlan, Failure> {
+    for (index, &joltage) in config.joltages.iter().enumerate() {
+        if joltage != 0 && !config.toggles_indices.iter().any(|b| b.contains(&index)) {
+            return Err(Failure::Infeasible(format!(
+                "Joltage {} is {} but no button increases it",
+                index, joltage
+            )));
+        }
     }
 
-    let model = optimizer.get_model().unwrap();
-    let total_presses: i64 = buttons_pressed
-        .iter()
-        .map(|button| model.eval(button, true).unwrap().as_i64().unwrap())
-        .sum();
+    let joltages: Vec<i64> = config.joltages.iter().map(|&j| j as i64).collect();
+    match ilp::min_sum_solution(&joltage_matrix(config), &joltages) {
+        Ok(presses) => {
+            let plan = PressPlan::replay(config, presses);
+            assert!(plan.reaches_joltages(config), "Wrong plan {:?}", plan);
+            Ok(plan)
+        }
+        Err(SolveError::Infeasible) => Err(Failure::Infeasible(format!(
+            "No whole number of presses reaches joltages {:?}",
+            config.joltages
+        ))),
+        Err(SolveError::Unknown(reason)) => Err(Failure::Unknown(reason)),
+    }
+}
+
+type Solve = fn(&Config) -> Result<PressPlan, Failure>;
 
-    total_presses as i32
+/// Solves the machines in parallel, with the time each one took.
+fn solve_each(configs: &[Config], solve: Solve) -> Vec<(Result<PressPlan, Failure>, Duration)> {
+    parallel::map(configs, |config| {
+        let start = Instant::now();
+        let result = solve(config);
+        (result, start.elapsed())
+    })
 }
 
-pub fn part1(input: &str) -> i32 {
-    let configs = parse_input(input);
-    let mut total_presses = 0;
-    for config in configs {
-        total_presses += solve_subproblem(config);
+/// Adds up the presses of every machine, collecting the ones without an answer.
+fn solve_all(input: &str, solve: Solve) -> PartResult {
+    let mut result = PartResult {
+        total: 0,
+        failures: Vec::new(),
+        timings: Vec::new(),
+    };
+    let outcomes = solve_each(&parse_input(input), solve);
+    for (i, (outcome, time)) in outcomes.into_iter().enumerate() {
+        match outcome {
+            Ok(plan) => result.total += plan.total(),
+            Err(failure) => result.failures.push((i + 1, failure)),
+        }
+        result.timings.push(time);
     }
-    total_presses
+    result
+}
+
+pub fn part1(input: &str) -> PartResult {
+    solve_all(input, solve_subproblem)
 }
 
-pub fn part2(input: &str) -> i32 {
+pub fn part2(input: &str) -> PartResult {
+    solve_all(input, solve_subproblem2)
+}
+
+/// Line numbers and times of the `n` machines that took longest, given the
+/// time of each machine in input order.
+fn slowest(timings: &[Duration], n: usize) -> Vec<(usize, Duration)> {
+    let mut lines: Vec<(usize, Duration)> = timings
+        .iter()
+        .enumerate()
+        .map(|(i, &time)| (i + 1, time))
+        .collect();
+    lines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    lines.truncate(n);
+    lines
+}
+
+/// Machines listed at the end of `explain` as taking longest.
+const SLOWEST_SHOWN: usize = 5;
+
+/// Shows the button presses for the lights and the joltages of every machine,
+/// how long finding them took, and which machines took longest.
+pub fn explain(input: &str) -> String {
     let configs = parse_input(input);
-    let mut total_presses = 0;
-    for config in configs {
-        total_presses += solve_subproblem2(config);
+    let lights = solve_each(&configs, solve_subproblem);
+    let joltages = solve_each(&configs, solve_subproblem2);
+
+    let mut out = String::new();
+    let mut timings = Vec::new();
+    for (i, ((lights, lights_time), (joltages, joltages_time))) in
+        lights.into_iter().zip(joltages).enumerate()
+    {
+        out.push_str(&format!("Machine {}\n", i + 1));
+        match lights {
+            Ok(lights) => {
+                let lights_on: String = lights
+                    .lights_on
+                    .iter()
+                    .map(|&on| if on { '#' } else { '.' })
+                    .collect();
+                out.push_str(&format!(
+                    "  Lights:   {} presses {:?} -> [{}]",
+                    lights.total(),
+                    lights.presses,
+                    lights_on
+                ));
+            }
+            Err(failure) => out.push_str(&format!("  Lights:   {}", failure)),
+        }
+        out.push_str(&format!(" in {:?}\n", lights_time));
+        match joltages {
+            Ok(joltages) => out.push_str(&format!(
+                "  Joltages: {} presses {:?} -> {:?}",
+                joltages.total(),
+                joltages.presses,
+                joltages.joltages
+            )),
+            Err(failure) => out.push_str(&format!("  Joltages: {}", failure)),
+        }
+        out.push_str(&format!(" in {:?}\n", joltages_time));
+        timings.push(lights_time + joltages_time);
+    }
+
+    out.push_str("Slowest machines\n");
+    for (line, time) in slowest(&timings, SLOWEST_SHOWN) {
+        out.push_str(&format!("  Line {}: {:?}\n", line, time));
     }
-    total_presses
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solver::{self, LinearExpr, Problem, Var};
 
     #[test]
     fn test_parse_line1() {
         let input = "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}";
-        let config = parse_line(input);
+        let config = parse_line(input).unwrap();
         assert_eq!(vec![false, true, true, false], config.lights_on);
         assert_eq!(
             vec![
@@ -177,10 +449,60 @@ mod tests {
         assert_eq!(vec![3, 5, 4, 7], config.joltages);
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", "Empty line"),
+            ("(0) {1,2}", "Column 1 \"(0)\": expected lights in [...]"),
+            ("[.x] (0) {1,2}", "Column 1 \"[.x]\": unexpected light 'x'"),
+            ("[] (0) {}", "Column 1 \"[]\": no lights"),
+            (
+                "[.#] (0) (2) {1,2}",
+                "Column 10 \"(2)\": light 2 does not exist, there are 2",
+            ),
+            (
+                "[.#] (0,1,0) {1,2}",
+                "Column 6 \"(0,1,0)\": light 0 listed twice",
+            ),
+            ("[.#] () {1,2}", "Column 6 \"()\": empty list"),
+            ("[.#] (a) {1,2}", "Column 6 \"(a)\": \"a\" is not a number"),
+            (
+                "[.#] (0) 1,2",
+                "Missing joltages {...} at the end of the line",
+            ),
+            (
+                "[.#] {1,2}",
+                "Missing buttons (...) between the lights and the joltages",
+            ),
+            (
+                "[.#] (0) {1,2,3}",
+                "Column 10 \"{1,2,3}\": 3 joltages for 2 lights",
+            ),
+            (
+                "[.#] (0) {1,-2}",
+                "Column 10 \"{1,-2}\": negative joltage -2",
+            ),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(
+                Err(expected.to_string()),
+                parse_line(line).map(|_| ()),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Line 2: Column 8 \"(7)\": light 7 does not exist, there are 4")]
+    fn test_parse_reports_line_number() {
+        parse_input("[.##.] (3) {0,0,0,1}\n[.##.] (7) {0,0,0,1}\n");
+    }
+
     #[test]
     fn test_part1_example() {
         let input = std::fs::read_to_string("inputs/day10/example1.txt").expect("Example file not found");
-        assert_eq!(7, part1(&input));
+        assert_eq!(7, part1(&input).total);
     }
 
     #[test]
@@ -192,7 +514,7 @@ mod tests {
     #[test]
     fn test_part2_example() {
         let input = std::fs::read_to_string("inputs/day10/example1.txt").expect("Example file not found");
-        assert_eq!(33, part2(&input));
+        assert_eq!(33, part2(&input).total);
     }
 
     #[test]
@@ -200,4 +522,231 @@ mod tests {
         let input = std::fs::read_to_string("inputs/day10/input.txt").expect("Input file not found");
         println!("Part 2: {}", part2(&input));
     }
+
+    #[test]
+    fn test_plans_reach_targets() {
+        let input =
+            std::fs::read_to_string("inputs/day10/example1.txt").expect("Example file not found");
+        for config in parse_input(&input) {
+            assert!(solve_subproblem(&config).unwrap().reaches_lights(&config));
+            assert!(
+                solve_subproblem2(&config)
+                    .unwrap()
+                    .reaches_joltages(&config)
+            );
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let config = parse_line("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}").unwrap();
+        let plan = PressPlan::replay(&config, vec![0, 1, 0, 0, 0, 1]);
+        assert_eq!(vec![true, false, false, true], plan.lights_on);
+        assert_eq!(vec![1, 2, 0, 1], plan.joltages);
+        assert!(!plan.reaches_lights(&config));
+
+        let plan = PressPlan::replay(&config, vec![0, 0, 0, 0, 1, 1]);
+        assert!(plan.reaches_lights(&config));
+        assert!(!plan.reaches_joltages(&config));
+
+        // A tampered state does not verify
+        let mut plan = PressPlan::replay(&config, vec![1, 3, 0, 3, 1, 2]);
+        assert!(plan.reaches_joltages(&config));
+        plan.presses[0] += 1;
+        assert!(!plan.reaches_joltages(&config));
+    }
+
+    #[test]
+    fn test_too_many_combinations() {
+        // Every button toggles the same light, so all but one are redundant
+        let line = format!("[#] {} {{1}}", "(0) ".repeat(MAX_NULLITY + 2));
+        let config = parse_line(&line).unwrap();
+        assert_eq!(
+            Err(Failure::Unknown(format!(
+                "2^{} ways to combine the buttons are too many to search",
+                MAX_NULLITY + 1
+            ))),
+            solve_subproblem(&config)
+        );
+
+        let line = format!("[#] {} {{1}}", "(0) ".repeat(MAX_NULLITY + 1));
+        let config = parse_line(&line).unwrap();
+        assert_eq!(1, solve_subproblem(&config).unwrap().total());
+
+        // Too many combinations, but no combination can work anyway
+        let line = format!("[#.] {} {{1,0}}", "(0,1) ".repeat(MAX_NULLITY + 2));
+        let config = parse_line(&line).unwrap();
+        assert_eq!(
+            Err(Failure::Infeasible(
+                "Every button toggles an even number of lights [0, 1], but an odd number of them are on"
+                    .to_string()
+            )),
+            solve_subproblem(&config)
+        );
+    }
+
+    #[test]
+    fn test_infeasible_machines() {
+        let config = parse_line("[.#.] (0) (2) {0,1,0}").unwrap();
+        assert_eq!(
+            Err(Failure::Infeasible(
+                "Light 1 is on but no button toggles it".to_string()
+            )),
+            solve_subproblem(&config)
+        );
+        assert_eq!(
+            Err(Failure::Infeasible(
+                "Joltage 1 is 1 but no button increases it".to_string()
+            )),
+            solve_subproblem2(&config)
+        );
+
+        // Lights 0 and 1 always change together
+        let config = parse_line("[#.#] (0,1) (0,1,2) {1,2,2}").unwrap();
+        assert_eq!(
+            Err(Failure::Infeasible(
+                "Every button toggles an even number of lights [0, 1], but an odd number of them are on"
+                    .to_string()
+            )),
+            solve_subproblem(&config)
+        );
+        assert!(matches!(
+            solve_subproblem2(&config),
+            Err(Failure::Infeasible(_))
+        ));
+    }
+
+    #[test]
+    fn test_part_result_reports_lines() {
+        let input =
+            "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}\n[#.#] (0,1) (0,1,2) {1,2,2}\n";
+        let result = part1(input);
+        assert_eq!(2, result.total);
+        assert_eq!(
+            vec![2],
+            result
+                .failures
+                .iter()
+                .map(|&(line, _)| line)
+                .collect::<Vec<_>>()
+        );
+        assert!(result.to_string().starts_with("2\nLine 2: infeasible: "));
+
+        let result = part2(input);
+        assert_eq!(10, result.total);
+        assert_eq!(1, result.failures.len());
+        assert_eq!(2, result.timings.len());
+    }
+
+    #[test]
+    fn test_slowest() {
+        let timings = [
+            Duration::from_millis(3),
+            Duration::from_millis(7),
+            Duration::from_millis(3),
+        ];
+        assert_eq!(
+            vec![(2, Duration::from_millis(7)), (1, Duration::from_millis(3))],
+            slowest(&timings, 2)
+        );
+        assert_eq!(3, slowest(&timings, 5).len());
+    }
+
+    #[test]
+    fn test_explain_example() {
+        let input =
+            std::fs::read_to_string("inputs/day10/example1.txt").expect("Example file not found");
+        let explanation = explain(&input);
+        assert_eq!(13, explanation.lines().count());
+        assert!(explanation.starts_with("Machine 1\n  Lights:   2 presses "));
+        assert_eq!(
+            3,
+            explanation
+                .lines()
+                .skip_while(|line| *line != "Slowest machines")
+                .filter(|line| line.starts_with("  Line "))
+                .count()
+        );
+    }
+
+    /// The part 2 problem for the general solver backend.
+    fn joltage_problem(config: &Config) -> Problem {
+        let mut problem = Problem::new();
+        let buttons_pressed: Vec<Var> = (0..config.toggles_indices.len())
+            .map(|i| problem.int_var(&format!("button_{}", i), 0, None))
+            .collect();
+
+        let mut joltage_incrementers = vec![LinearExpr::new(); config.joltages.len()];
+        for (&button, button_indices) in buttons_pressed.iter().zip(&config.toggles_indices) {
+            for &joltage_index in button_indices {
+                joltage_incrementers[joltage_index].add_term(button, 1);
+            }
+        }
+        for (joltage_sum, &joltage) in joltage_incrementers.into_iter().zip(&config.joltages) {
+            problem.equal(joltage_sum, joltage as i64);
+        }
+
+        problem.minimize(LinearExpr::sum(&buttons_pressed));
+        problem
+    }
+
+    /// Machines with a known way to reach the joltages, from a fixed seed.
+    fn generated_machines(count: usize) -> Vec<Config> {
+        let mut state: u64 = 2025;
+        let mut next = |bound: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+
+        (0..count)
+            .map(|_| {
+                let lights = 2 + next(5) as usize;
+                let buttons = 2 + next(6) as usize;
+                let toggles_indices: Vec<Vec<usize>> = (0..buttons)
+                    .map(|_| {
+                        let mut indices: Vec<usize> =
+                            (0..lights).filter(|_| next(2) == 0).collect();
+                        if indices.is_empty() {
+                            indices.push(next(lights as u64) as usize);
+                        }
+                        indices
+                    })
+                    .collect();
+                let mut joltages = vec![0; lights];
+                for indices in &toggles_indices {
+                    let presses = next(8) as i32;
+                    for &i in indices {
+                        joltages[i] += presses;
+                    }
+                }
+                Config {
+                    lights_on: vec![false; lights],
+                    toggles_indices,
+                    joltages,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_part2_matches_solver_backend() {
+        let backend = solver::default_backend();
+        for config in generated_machines(40) {
+            let expected = backend
+                .solve(&joltage_problem(&config))
+                .expect("Generated machine is solvable")
+                .objective;
+            let plan = solve_subproblem2(&config).unwrap();
+            assert!(plan.reaches_joltages(&config));
+            assert_eq!(
+                expected,
+                plan.total(),
+                "{} disagrees on {:?}",
+                backend.name(),
+                config
+            );
+        }
+    }
 }
@@ -1,102 +1,36 @@
-use std::collections::HashMap;
+use crate::graph::{Graph, GraphBuilder};
 
-struct Graph<'a> {
-    edges: HashMap<&'a str, Vec<&'a str>>,
-}
-
-fn parse_input(input: &str) -> Graph<'_> {
-    let mut graph = Graph {
-        edges: HashMap::new(),
-    };
+fn parse_input(input: &str) -> Graph {
+    let mut builder = GraphBuilder::new();
 
     for line in input.lines() {
         let parts: Vec<&str> = line.split_whitespace().collect();
-        let key = parts[0].trim_end_matches(":");
-        let values = parts[1..].to_vec();
-        graph.edges.insert(key, values);
-    }
-
-    graph
-}
-
-fn path_count(graph: &Graph, start: &str) -> usize {
-    let outputs = match graph.edges.get(start) {
-        Some(v) => v,
-        None => panic!("No edges for node {}", start),
-    };
-
-    if outputs.len() == 1 && outputs[0] == "out" {
-        return 1;
-    }
-
-    let mut total = 0;
-    for &node in outputs {
-        total += path_count(graph, node);
+        let key = builder.node(parts[0].trim_end_matches(":"));
+        for &value in &parts[1..] {
+            let value = builder.node(value);
+            builder.add_edge(key, value);
+        }
     }
 
-    total
+    builder.build()
 }
 
-struct Cache {
-    paths: HashMap<(String, bool, bool), usize>,
-}
-
-fn path_count_through(
-    graph: &Graph,
-    cache: &mut Cache,
-    path: String,
-    start: &str,
-    through_dac: bool,
-    through_fft: bool,
-) -> usize {
-    let cache_key = (start.to_string(), through_dac, through_fft);
-    if let Some(&count) = cache.paths.get(&cache_key) {
-        println!("Cache hit for node {}: {}", start, count);
-        return count;
-    }
-
-    let outputs = match graph.edges.get(start) {
-        Some(v) => v,
-        None => panic!("No edges for node {}", start),
-    };
-
-    if outputs.len() == 1 && outputs[0] == "out" {
-        if through_dac && through_fft {
-            println!("Reached OUT: {}, Count: 1", path);
-            cache.paths.insert(cache_key, 1);
-            return 1;
-        } else {
-            return 0;
-        }
-    }
-
-    let mut total = 0;
-    for &node in outputs {
-        let path = format!("{path}/{node}");
-        let through_dac = through_dac || node == "dac";
-        let through_fft = through_fft || node == "fft";
-        total += path_count_through(graph, cache, path, node, through_dac, through_fft);
+fn node(graph: &Graph, name: &str) -> usize {
+    match graph.id(name) {
+        Some(id) => id,
+        None => panic!("No node named {}", name),
     }
-
-    println!("Path: {}, Count: {}", path, total);
-    cache.paths.insert(cache_key, total);
-
-    total
 }
 
 pub fn part1(input: &str) -> usize {
     let graph = parse_input(input);
-    let count = path_count(&graph, "you");
-    count
+    graph.path_count(node(&graph, "you"), node(&graph, "out"))
 }
 
 pub fn part2(input: &str) -> usize {
     let graph = parse_input(input);
-    let mut cache = Cache {
-        paths: HashMap::new(),
-    };
-    let count = path_count_through(&graph, &mut cache, "/svr".to_string(), "svr", false, false);
-    count
+    let via = [node(&graph, "dac"), node(&graph, "fft")];
+    graph.path_count_via(node(&graph, "svr"), node(&graph, "out"), &via)
 }
 
 #[cfg(test)]
@@ -1,70 +1,136 @@
+use std::collections::BTreeMap;
+
+use crate::packing;
+use crate::polyomino::Shape;
+
+struct Region {
+    width: usize,
+    height: usize,
+    /// How many of each shape, in the same order as `Input::shapes`
+    counts: Vec<usize>,
+}
+
 struct Input {
-    grids: Vec<((i32, i32), Vec<i32>)>,
+    /// Sorted by their declared `N:` label
+    shapes: Vec<Shape>,
+    regions: Vec<Region>,
 }
 
-fn parse_input(input: &str) -> Input {
-    let mut grids = Vec::new();
-    let mut lines = input.lines().peekable();
-
-    let mut present_size: i32 = 0;
-    loop {
-        let line = match lines.peek() {
-            Some(&l) => l,
-            None => panic!("No grid size line found"),
-        };
+fn parse_number(line_number: usize, s: &str) -> usize {
+    match s.trim().parse() {
+        Ok(v) => v,
+        Err(_) => panic!("Line {}: invalid number {:?}", line_number, s),
+    }
+}
 
-        if line.trim().is_empty() {
-            lines.next();
+fn parse_input(input: &str) -> Input {
+    let mut shapes: BTreeMap<usize, String> = BTreeMap::new();
+    // Region counts by shape label, with the line they came from
+    let mut regions: Vec<(usize, Region)> = Vec::new();
+    let mut current_shape: Option<usize> = None;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            current_shape = None;
             continue;
         }
 
-        if line.contains("x") {
-            break;
+        let (head, rest) = match line.split_once(':') {
+            Some(parts) => parts,
+            None => {
+                // A row of the shape being declared
+                let label = match current_shape {
+                    Some(label) => label,
+                    None => panic!("Line {}: unexpected line {:?}", line_number, line),
+                };
+                if let Some(c) = line.chars().find(|&c| c != '#' && c != '.') {
+                    panic!(
+                        "Line {}: unexpected character {:?} in shape",
+                        line_number, c
+                    );
+                }
+                let block = shapes.get_mut(&label).unwrap();
+                block.push_str(line);
+                block.push('\n');
+                continue;
+            }
+        };
+        current_shape = None;
+
+        // "WxH: counts..." starts a region, "N:" declares a shape
+        if let Some((width, height)) = head.split_once('x') {
+            let region = Region {
+                width: parse_number(line_number, width),
+                height: parse_number(line_number, height),
+                counts: rest
+                    .split_whitespace()
+                    .map(|count| parse_number(line_number, count))
+                    .collect(),
+            };
+            regions.push((line_number, region));
+            continue;
         }
 
-        lines.next();
-        present_size += line.trim().matches('#').count() as i32;
-    }
-
-    for line in lines {
-        let parts: Vec<&str> = line.split(":").collect();
-        let grid_size: Vec<i32> = parts[0]
-            .trim()
-            .split("x")
-            .map(|s| match s.parse() {
-                Ok(v) => v,
-                Err(_) => panic!("Invalid grid size value: {}", s),
-            })
-            .collect();
-        if grid_size.len() != 2 {
-            panic!("Invalid grid size: {}", parts[0]);
+        if !rest.trim().is_empty() {
+            panic!("Line {}: unexpected text after shape label", line_number);
         }
-
-        let present_counts: Vec<i32> = parts[1]
-            .split_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
-
-        if present_counts.len() != 6 {
-            panic!("Invalid present counts: {}", parts[1]);
+        if !regions.is_empty() {
+            panic!("Line {}: shape declared after the regions", line_number);
+        }
+        let label = parse_number(line_number, head);
+        if shapes.insert(label, String::new()).is_some() {
+            panic!("Line {}: shape {} is declared twice", line_number, label);
         }
-        grids.push(((grid_size[0], grid_size[1]), present_counts));
+        current_shape = Some(label);
     }
 
-    Input { grids }
+    if regions.is_empty() {
+        panic!("No regions found");
+    }
+
+    let labels: Vec<usize> = shapes.keys().copied().collect();
+    let shapes: Vec<Shape> = shapes
+        .iter()
+        .map(|(label, block)| {
+            if block.is_empty() {
+                panic!("Shape {} has no rows", label);
+            }
+            Shape::parse(block)
+        })
+        .collect();
+
+    let regions = regions
+        .into_iter()
+        .map(|(line_number, region)| {
+            for (label, &count) in region.counts.iter().enumerate() {
+                if count > 0 && !labels.contains(&label) {
+                    panic!(
+                        "Line {}: region uses shape {} which is not declared",
+                        line_number, label
+                    );
+                }
+            }
+            Region {
+                counts: labels
+                    .iter()
+                    .map(|&label| region.counts.get(label).copied().unwrap_or(0))
+                    .collect(),
+                ..region
+            }
+        })
+        .collect();
+
+    Input { shapes, regions }
 }
 
 pub fn part1(input: &str) -> i32 {
     let input = parse_input(input);
 
     let mut filled_regions = 0;
-    for ((width, height), presents) in &input.grids {
-        let width = width / 3;
-        let height = height / 3;
-        let total_present_size = presents.iter().sum::<i32>();
-
-        let presents_fit = total_present_size <= width * height;
-        if presents_fit {
+    for region in &input.regions {
+        if packing::fits(&input.shapes, &region.counts, region.width, region.height) {
             filled_regions += 1;
         }
     }
@@ -72,18 +138,87 @@ pub fn part1(input: &str) -> i32 {
     filled_regions
 }
 
+/// Shows how the presents are packed in every region.
+pub fn explain(input: &str) -> String {
+    let input = parse_input(input);
+
+    let mut out = String::new();
+    for region in &input.regions {
+        out.push_str(&format!(
+            "{}x{}: {:?}\n",
+            region.width, region.height, region.counts
+        ));
+        match packing::pack(&input.shapes, &region.counts, region.width, region.height) {
+            Some(placements) => out.push_str(&packing::render(
+                &input.shapes,
+                &placements,
+                region.width,
+                region.height,
+            )),
+            None => out.push_str("Does not fit\n"),
+        }
+        out.push('\n');
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    #[ignore = "Not working, since the solution is just heuristic"]
+    fn test_parse_shapes() {
+        let input =
+            std::fs::read_to_string("inputs/day12/example1.txt").expect("Example file not found");
+        let input = parse_input(&input);
+        assert_eq!(6, input.shapes.len());
+        assert_eq!("###\n#..\n###\n", input.shapes[4].to_string());
+        assert_eq!(3, input.regions.len());
+        assert_eq!(vec![1, 0, 1, 0, 2, 2], input.regions[1].counts);
+    }
+
+    #[test]
+    fn test_parse_labels_and_sizes() {
+        // Labels need not start at 0 or be contiguous, and shapes can have any size
+        let input = "7:\n####\n\n2:\n#\n#\n\n6x1: 0 0 1 0 0 0 0 1\n5x1: 0 0 1 0 0 0 0 1\n4x4: 0 0 0 0 0 0 0 4 0 0\n";
+        assert_eq!(2, part1(input));
+
+        let input = parse_input(input);
+        assert_eq!(2, input.shapes.len());
+        assert_eq!((1, 2), input.shapes[0].bounding_box());
+        assert_eq!((4, 1), input.shapes[1].bounding_box());
+        assert_eq!(vec![1, 1], input.regions[0].counts);
+        assert_eq!(vec![0, 4], input.regions[2].counts);
+    }
+
+    #[test]
+    #[should_panic(expected = "Line 4: region uses shape 1 which is not declared")]
+    fn test_parse_undeclared_shape() {
+        parse_input("0:\n##\n\n2x2: 1 1\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "Line 2: unexpected character 'x' in shape")]
+    fn test_parse_invalid_shape_row() {
+        parse_input("0:\n#x#\n\n2x2: 1\n");
+    }
+
+    #[test]
     fn test_part1_example() {
         let input =
             std::fs::read_to_string("inputs/day12/example1.txt").expect("Example file not found");
         assert_eq!(2, part1(&input));
     }
 
+    #[test]
+    fn test_explain_example() {
+        let input =
+            std::fs::read_to_string("inputs/day12/example1.txt").expect("Example file not found");
+        let explanation = explain(&input);
+        assert_eq!(1, explanation.matches("Does not fit").count());
+        assert!(explanation.starts_with("4x4: [0, 0, 0, 0, 2, 0]\n"));
+    }
+
     #[test]
     fn test_part1_input() {
         let input =
@@ -0,0 +1,414 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Point { x, y }
+    }
+
+    fn doubled(self) -> Point {
+        Point::new(self.x * 2, self.y * 2)
+    }
+}
+
+/// Whether the edge of a shape counts as part of it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Boundary {
+    Open,
+    Closed,
+}
+
+/// Horizontal or vertical line segment, including both end points.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+impl Segment {
+    pub fn new(a: Point, b: Point) -> Self {
+        if a.x != b.x && a.y != b.y {
+            panic!("Segment {:?} -> {:?} is not axis-aligned", a, b);
+        }
+        Segment { a, b }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.a.y == self.b.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.a.x == self.b.x
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::from_corners(self.a, self.b)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.bounds().contains(p, Boundary::Closed)
+    }
+}
+
+/// Axis-aligned rectangle spanning `min` to `max`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rect {
+    pub fn from_corners(p1: Point, p2: Point) -> Self {
+        Rect {
+            min: Point::new(p1.x.min(p2.x), p1.y.min(p2.y)),
+            max: Point::new(p1.x.max(p2.x), p1.y.max(p2.y)),
+        }
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.y - self.min.y
+    }
+
+    /// Geometric area, zero for degenerate rectangles.
+    pub fn area(&self) -> i64 {
+        self.width() * self.height()
+    }
+
+    /// Number of integer grid points covered, i.e. the area counted in tiles.
+    pub fn cell_count(&self) -> i64 {
+        (self.width() + 1) * (self.height() + 1)
+    }
+
+    pub fn contains(&self, p: Point, boundary: Boundary) -> bool {
+        match boundary {
+            Boundary::Open => {
+                self.min.x < p.x && p.x < self.max.x && self.min.y < p.y && p.y < self.max.y
+            }
+            Boundary::Closed => {
+                self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+            }
+        }
+    }
+
+    /// Whether the segment touches the rectangle. With `Boundary::Open` only
+    /// the interior counts, so a segment running along an edge does not intersect.
+    pub fn intersects_segment(&self, segment: &Segment, boundary: Boundary) -> bool {
+        let s = segment.bounds();
+        match boundary {
+            Boundary::Open => {
+                s.min.x < self.max.x
+                    && self.min.x < s.max.x
+                    && s.min.y < self.max.y
+                    && self.min.y < s.max.y
+            }
+            Boundary::Closed => {
+                s.min.x <= self.max.x
+                    && self.min.x <= s.max.x
+                    && s.min.y <= self.max.y
+                    && self.min.y <= s.max.y
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    Degenerate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    OnBoundary,
+    Outside,
+}
+
+/// Twice the signed area of the polygon (shoelace formula). Positive when the
+/// vertices go counter-clockwise in a y-up coordinate system.
+pub fn signed_area2(vertices: &[Point]) -> i64 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum()
+}
+
+/// Polygon area, rounded down for polygons with half-integer area.
+pub fn polygon_area(vertices: &[Point]) -> i64 {
+    signed_area2(vertices).abs() / 2
+}
+
+pub fn orientation(vertices: &[Point]) -> Orientation {
+    match signed_area2(vertices) {
+        a if a > 0 => Orientation::CounterClockwise,
+        a if a < 0 => Orientation::Clockwise,
+        _ => Orientation::Degenerate,
+    }
+}
+
+/// Ray casting towards +x, with exact integer arithmetic.
+pub fn point_in_polygon(p: Point, vertices: &[Point]) -> Location {
+    let n = vertices.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+
+        let cross = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+        let within = a.x.min(b.x) <= p.x
+            && p.x <= a.x.max(b.x)
+            && a.y.min(b.y) <= p.y
+            && p.y <= a.y.max(b.y);
+        if cross == 0 && within {
+            return Location::OnBoundary;
+        }
+
+        if (a.y > p.y) != (b.y > p.y) {
+            // The edge crosses the ray's line; check if it is to the right of p.
+            // Same sign test as p.x < intersection x, without the division.
+            let right_of_p = if b.y > a.y { cross > 0 } else { cross < 0 };
+            if right_of_p {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Simple polygon whose edges are all horizontal or vertical.
+pub struct RectilinearPolygon {
+    vertices: Vec<Point>,
+    edges: Vec<Segment>,
+    doubled: Vec<Point>,
+}
+
+impl RectilinearPolygon {
+    /// Vertices in order; the last one connects back to the first.
+    pub fn new(vertices: Vec<Point>) -> Self {
+        if vertices.len() < 4 {
+            panic!("A rectilinear polygon needs at least 4 vertices");
+        }
+
+        let n = vertices.len();
+        let edges = (0..n)
+            .map(|i| Segment::new(vertices[i], vertices[(i + 1) % n]))
+            .collect();
+        let doubled = vertices.iter().map(|p| p.doubled()).collect();
+
+        RectilinearPolygon {
+            vertices,
+            edges,
+            doubled,
+        }
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> &[Segment] {
+        &self.edges
+    }
+
+    pub fn area(&self) -> i64 {
+        polygon_area(&self.vertices)
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        orientation(&self.vertices)
+    }
+
+    pub fn locate(&self, p: Point) -> Location {
+        point_in_polygon(p, &self.vertices)
+    }
+
+    /// Whether the closed rectangle lies within the closed polygon.
+    pub fn contains_rect(&self, rect: &Rect) -> bool {
+        if rect.width() == 0 || rect.height() == 0 {
+            return self.contains_degenerate(rect);
+        }
+
+        // Every edge has the outside on one side, so an edge through the
+        // interior means part of the rectangle is outside
+        if self
+            .edges
+            .iter()
+            .any(|e| rect.intersects_segment(e, Boundary::Open))
+        {
+            return false;
+        }
+
+        // Without edges inside, the interior is either all in or all out
+        let center = Point::new(rect.min.x + rect.max.x, rect.min.y + rect.max.y);
+        point_in_polygon(center, &self.doubled) == Location::Inside
+    }
+
+    /// A rectangle without width or height is a segment or point. Its status only
+    /// changes at polygon vertex coordinates, so test those and the midpoints between.
+    fn contains_degenerate(&self, rect: &Rect) -> bool {
+        let mut stops: Vec<Point> = vec![rect.min.doubled(), rect.max.doubled()];
+        for v in &self.vertices {
+            if rect.width() > 0 && rect.min.x < v.x && v.x < rect.max.x {
+                stops.push(Point::new(v.x, rect.min.y).doubled());
+            }
+            if rect.height() > 0 && rect.min.y < v.y && v.y < rect.max.y {
+                stops.push(Point::new(rect.min.x, v.y).doubled());
+            }
+        }
+        stops.sort_unstable_by_key(|p| (p.x, p.y));
+        stops.dedup();
+
+        let mut samples = stops.clone();
+        for w in stops.windows(2) {
+            samples.push(Point::new((w[0].x + w[1].x) / 2, (w[0].y + w[1].y) / 2));
+        }
+
+        samples
+            .iter()
+            .all(|&p| point_in_polygon(p, &self.doubled) != Location::Outside)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(x: i64, y: i64) -> Point {
+        Point::new(x, y)
+    }
+
+    fn rect(p1: Point, p2: Point) -> Rect {
+        Rect::from_corners(p1, p2)
+    }
+
+    #[test]
+    fn test_cell_count() {
+        assert_eq!(24, rect(p(2, 5), p(9, 7)).cell_count());
+        assert_eq!(35, rect(p(7, 1), p(11, 7)).cell_count());
+        assert_eq!(24, rect(p(7, 1), p(11, 7)).area());
+    }
+
+    #[test]
+    fn test_segment_crossing_rect() {
+        let segment = Segment::new(p(9, 5), p(2, 5));
+        assert!(rect(p(7, 1), p(11, 7)).intersects_segment(&segment, Boundary::Open));
+    }
+
+    #[test]
+    fn test_segment_crossing_rect_from_outside() {
+        let segment = Segment::new(p(0, 1), p(5, 1));
+        assert!(rect(p(1, 0), p(3, 2)).intersects_segment(&segment, Boundary::Open));
+    }
+
+    #[test]
+    fn test_segment_spanning_rect_between_borders() {
+        let segment = Segment::new(p(1, 1), p(3, 1));
+        assert!(rect(p(1, 0), p(3, 2)).intersects_segment(&segment, Boundary::Open));
+
+        let segment = Segment::new(p(7, 1), p(7, 3));
+        assert!(rect(p(11, 1), p(2, 3)).intersects_segment(&segment, Boundary::Open));
+    }
+
+    #[test]
+    fn test_segment_on_rect_edge() {
+        let r = rect(p(1, 0), p(3, 2));
+        let along_edge = Segment::new(p(0, 2), p(5, 2));
+        assert!(!r.intersects_segment(&along_edge, Boundary::Open));
+        assert!(r.intersects_segment(&along_edge, Boundary::Closed));
+
+        let touching_corner = Segment::new(p(3, 2), p(3, 5));
+        assert!(!r.intersects_segment(&touching_corner, Boundary::Open));
+        assert!(r.intersects_segment(&touching_corner, Boundary::Closed));
+
+        let poking_in = Segment::new(p(2, 2), p(2, 1));
+        assert!(r.intersects_segment(&poking_in, Boundary::Open));
+
+        let outside = Segment::new(p(4, 0), p(4, 2));
+        assert!(!r.intersects_segment(&outside, Boundary::Closed));
+    }
+
+    #[test]
+    fn test_orientation_and_area() {
+        let square = vec![p(0, 0), p(4, 0), p(4, 3), p(0, 3)];
+        assert_eq!(Orientation::CounterClockwise, orientation(&square));
+        assert_eq!(12, polygon_area(&square));
+
+        let reversed: Vec<Point> = square.iter().rev().copied().collect();
+        assert_eq!(Orientation::Clockwise, orientation(&reversed));
+        assert_eq!(-24, signed_area2(&reversed));
+
+        assert_eq!(
+            Orientation::Degenerate,
+            orientation(&[p(0, 0), p(1, 1), p(2, 2)])
+        );
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        // L-shape
+        let polygon = vec![p(0, 0), p(4, 0), p(4, 2), p(2, 2), p(2, 4), p(0, 4)];
+        assert_eq!(Location::Inside, point_in_polygon(p(1, 1), &polygon));
+        assert_eq!(Location::Inside, point_in_polygon(p(1, 3), &polygon));
+        assert_eq!(Location::Outside, point_in_polygon(p(3, 3), &polygon));
+        assert_eq!(Location::OnBoundary, point_in_polygon(p(2, 3), &polygon));
+        assert_eq!(Location::OnBoundary, point_in_polygon(p(4, 0), &polygon));
+        assert_eq!(Location::Outside, point_in_polygon(p(-1, 2), &polygon));
+        assert_eq!(Location::Outside, point_in_polygon(p(5, 0), &polygon));
+    }
+
+    #[test]
+    fn test_contains_rect() {
+        let polygon =
+            RectilinearPolygon::new(vec![p(0, 0), p(4, 0), p(4, 2), p(2, 2), p(2, 4), p(0, 4)]);
+        assert_eq!(12, polygon.area());
+        assert!(polygon.contains_rect(&rect(p(0, 0), p(4, 2))));
+        assert!(polygon.contains_rect(&rect(p(0, 0), p(2, 4))));
+        assert!(!polygon.contains_rect(&rect(p(0, 0), p(4, 4))));
+        assert!(!polygon.contains_rect(&rect(p(1, 1), p(3, 3))));
+        assert!(!polygon.contains_rect(&rect(p(3, 3), p(4, 4))));
+    }
+
+    #[test]
+    fn test_contains_degenerate_rect() {
+        let polygon =
+            RectilinearPolygon::new(vec![p(0, 0), p(4, 0), p(4, 2), p(2, 2), p(2, 4), p(0, 4)]);
+        assert!(polygon.contains_rect(&rect(p(0, 2), p(4, 2))));
+        assert!(polygon.contains_rect(&rect(p(2, 0), p(2, 4))));
+        assert!(!polygon.contains_rect(&rect(p(0, 3), p(4, 3))));
+        assert!(polygon.contains_rect(&rect(p(3, 1), p(3, 1))));
+        assert!(!polygon.contains_rect(&rect(p(3, 3), p(3, 3))));
+    }
+
+    #[test]
+    fn test_contains_rect_in_u_shape() {
+        // The gap between the arms is outside, even though all corners are inside
+        let polygon = RectilinearPolygon::new(vec![
+            p(0, 0),
+            p(6, 0),
+            p(6, 4),
+            p(4, 4),
+            p(4, 1),
+            p(2, 1),
+            p(2, 4),
+            p(0, 4),
+        ]);
+        assert!(!polygon.contains_rect(&rect(p(0, 4), p(6, 0))));
+        assert!(!polygon.contains_rect(&rect(p(1, 3), p(5, 3))));
+        assert!(polygon.contains_rect(&rect(p(0, 0), p(6, 1))));
+    }
+}
@@ -0,0 +1,360 @@
+//! Linear algebra over GF(2), the field with elements 0 and 1 where addition
+//! is XOR. Vectors and matrix rows are packed 64 bits to a word.
+
+use std::fmt;
+
+const WORD_BITS: usize = 64;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVec {
+    pub fn zeros(len: usize) -> Self {
+        BitVec {
+            len,
+            words: vec![0; len.div_ceil(WORD_BITS)],
+        }
+    }
+
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut v = BitVec::zeros(bits.len());
+        for (i, &bit) in bits.iter().enumerate() {
+            v.set(i, bit);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(
+            i < self.len,
+            "Bit {} out of range for length {}",
+            i,
+            self.len
+        );
+        self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(
+            i < self.len,
+            "Bit {} out of range for length {}",
+            i,
+            self.len
+        );
+        let mask = 1 << (i % WORD_BITS);
+        if value {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// Adds `other` to `self`, element-wise XOR.
+    pub fn xor_with(&mut self, other: &BitVec) {
+        assert_eq!(self.len, other.len, "Length mismatch");
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    /// Number of ones, the Hamming weight.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Dot product, the parity of the common ones.
+    pub fn dot(&self, other: &BitVec) -> bool {
+        assert_eq!(self.len, other.len, "Length mismatch");
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.get(i))
+    }
+
+    pub fn to_bools(&self) -> Vec<bool> {
+        (0..self.len).map(|i| self.get(i)).collect()
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.len {
+            write!(f, "{}", if self.get(i) { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitMatrix {
+    cols: usize,
+    rows: Vec<BitVec>,
+}
+
+impl BitMatrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        BitMatrix {
+            cols,
+            rows: vec![BitVec::zeros(cols); rows],
+        }
+    }
+
+    pub fn from_rows(cols: usize, rows: Vec<BitVec>) -> Self {
+        for row in &rows {
+            assert_eq!(cols, row.len(), "Row length does not match column count");
+        }
+        BitMatrix { cols, rows }
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn col_count(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, row: usize) -> &BitVec {
+        &self.rows[row]
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row].get(col)
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        self.rows[row].set(col, value);
+    }
+
+    pub fn transpose(&self) -> BitMatrix {
+        let mut out = BitMatrix::zeros(self.cols, self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in row.ones() {
+                out.set(j, i, true);
+            }
+        }
+        out
+    }
+
+    pub fn mul_vec(&self, x: &BitVec) -> BitVec {
+        let mut out = BitVec::zeros(self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            out.set(i, row.dot(x));
+        }
+        out
+    }
+
+    /// Brings the matrix to reduced row echelon form in place and returns the
+    /// pivot column of each nonzero row. Zero rows end up at the bottom.
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        self.row_reduce_with(&mut [])
+    }
+
+    /// Row reduction that applies the same row operations to `rhs`.
+    fn row_reduce_with(&mut self, rhs: &mut [bool]) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        for col in 0..self.cols {
+            let row = pivots.len();
+            let Some(found) = (row..self.rows.len()).find(|&r| self.rows[r].get(col)) else {
+                continue;
+            };
+            self.rows.swap(row, found);
+            if !rhs.is_empty() {
+                rhs.swap(row, found);
+            }
+
+            let pivot_row = self.rows[row].clone();
+            for r in 0..self.rows.len() {
+                if r != row && self.rows[r].get(col) {
+                    self.rows[r].xor_with(&pivot_row);
+                    if !rhs.is_empty() {
+                        rhs[r] ^= rhs[row];
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// A basis of the vectors `x` with `A·x = 0`, one per free column.
+    pub fn null_space(&self) -> Vec<BitVec> {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+        reduced.null_space_from_reduced(&pivots)
+    }
+
+    fn null_space_from_reduced(&self, pivots: &[usize]) -> Vec<BitVec> {
+        let mut is_pivot = vec![false; self.cols];
+        for &col in pivots {
+            is_pivot[col] = true;
+        }
+
+        let mut basis = Vec::new();
+        for free in (0..self.cols).filter(|&col| !is_pivot[col]) {
+            let mut v = BitVec::zeros(self.cols);
+            v.set(free, true);
+            for (row, &pivot) in pivots.iter().enumerate() {
+                if self.rows[row].get(free) {
+                    v.set(pivot, true);
+                }
+            }
+            basis.push(v);
+        }
+        basis
+    }
+
+    /// Some `x` with `A·x = b` plus a basis of the null space, so that every
+    /// solution is `x` plus a combination of the basis. `None` if there is no
+    /// solution.
+    pub fn solve(&self, b: &BitVec) -> Option<(BitVec, Vec<BitVec>)> {
+        assert_eq!(self.rows.len(), b.len(), "Right-hand side has wrong length");
+        let mut reduced = self.clone();
+        let mut rhs = b.to_bools();
+        let pivots = reduced.row_reduce_with(&mut rhs);
+
+        // Rows below the pivots are zero, so their right-hand side must be too
+        if rhs[pivots.len()..].iter().any(|&bit| bit) {
+            return None;
+        }
+
+        // Free variables set to zero
+        let mut x = BitVec::zeros(self.cols);
+        for (row, &pivot) in pivots.iter().enumerate() {
+            x.set(pivot, rhs[row]);
+        }
+        Some((x, reduced.null_space_from_reduced(&pivots)))
+    }
+
+    /// The solution of `A·x = b` with the fewest ones. Tries every combination
+    /// of the null space basis, so it is exponential in the nullity.
+    pub fn min_weight_solution(&self, b: &BitVec) -> Option<BitVec> {
+        let (x, basis) = self.solve(b)?;
+        Some(min_weight_in_coset(x, &basis))
+    }
+}
+
+/// The vector with the fewest ones among `x` plus any combination of `basis`.
+/// Exponential in the size of the basis.
+pub fn min_weight_in_coset(mut x: BitVec, basis: &[BitVec]) -> BitVec {
+    if basis.len() >= WORD_BITS {
+        panic!(
+            "Null space of dimension {} is too large to search",
+            basis.len()
+        );
+    }
+
+    // Walk the combinations in Gray code order, one basis vector changes per step
+    let mut best = x.clone();
+    for step in 1u64..1 << basis.len() {
+        x.xor_with(&basis[step.trailing_zeros() as usize]);
+        if x.count_ones() < best.count_ones() {
+            best = x.clone();
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&str]) -> BitMatrix {
+        let cols = rows.first().map_or(0, |r| r.len());
+        BitMatrix::from_rows(cols, rows.iter().map(|r| bits(r)).collect())
+    }
+
+    fn bits(s: &str) -> BitVec {
+        BitVec::from_bools(&s.chars().map(|c| c == '1').collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_bitvec() {
+        let mut v = BitVec::zeros(130);
+        v.set(0, true);
+        v.set(64, true);
+        v.set(129, true);
+        assert_eq!(3, v.count_ones());
+        assert_eq!(vec![0, 64, 129], v.ones().collect::<Vec<_>>());
+        v.xor_with(&v.clone());
+        assert!(v.is_zero());
+        assert_eq!("0110", format!("{:?}", bits("0110")));
+    }
+
+    #[test]
+    fn test_row_reduce() {
+        let mut m = matrix(&["110", "011", "101"]);
+        assert_eq!(vec![0, 1], m.row_reduce());
+        assert_eq!(matrix(&["101", "011", "000"]), m);
+        assert_eq!(3, matrix(&["100", "010", "001"]).rank());
+        assert_eq!(0, BitMatrix::zeros(2, 3).rank());
+    }
+
+    #[test]
+    fn test_null_space() {
+        let m = matrix(&["1100", "0110"]);
+        let basis = m.null_space();
+        assert_eq!(2, basis.len());
+        for v in &basis {
+            assert!(m.mul_vec(v).is_zero());
+        }
+        assert!(matrix(&["10", "01"]).null_space().is_empty());
+    }
+
+    #[test]
+    fn test_transpose() {
+        let m = matrix(&["110", "001"]);
+        assert_eq!(matrix(&["10", "10", "01"]), m.transpose());
+        assert_eq!(m, m.transpose().transpose());
+    }
+
+    #[test]
+    fn test_solve() {
+        let m = matrix(&["110", "011"]);
+        let b = bits("11");
+        let (x, basis) = m.solve(&b).unwrap();
+        assert_eq!(b, m.mul_vec(&x));
+        assert_eq!(1, basis.len());
+
+        // Rows sum to zero, so the right-hand side must too
+        let m = matrix(&["11", "11"]);
+        assert!(m.solve(&bits("10")).is_none());
+        assert!(m.solve(&bits("11")).is_some());
+    }
+
+    #[test]
+    fn test_min_weight_solution() {
+        // Columns are the buttons of the first day10 example machine
+        let m = matrix(&["000011", "010001", "001110", "110100"]);
+        let b = bits("0110");
+        let x = m.min_weight_solution(&b).unwrap();
+        assert_eq!(b, m.mul_vec(&x));
+        assert_eq!(2, x.count_ones());
+    }
+}
@@ -0,0 +1,511 @@
+use std::collections::HashMap;
+
+/// Dense index of a node, as handed out by `GraphBuilder::node`.
+pub type NodeId = usize;
+
+/// Collects named nodes and edges, and turns them into a compact `Graph`.
+#[derive(Default)]
+pub struct GraphBuilder {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    edges: Vec<(NodeId, NodeId)>,
+}
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `name`, interning it if it has not been seen before.
+    pub fn node(&mut self, name: &str) -> NodeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Adds a new node even if `name` is already taken, in which case `id`
+    /// keeps finding the first one.
+    fn add_node(&mut self, name: String) -> NodeId {
+        let id = self.names.len();
+        self.ids.entry(name.clone()).or_insert(id);
+        self.names.push(name);
+        id
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId) {
+        self.edges.push((from, to));
+    }
+
+    pub fn build(self) -> Graph {
+        let n = self.names.len();
+
+        // Compressed sparse rows: the successors of node i are
+        // targets[offsets[i]..offsets[i + 1]], in insertion order
+        let mut offsets = vec![0; n + 1];
+        for &(from, _) in &self.edges {
+            offsets[from + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut next = offsets.clone();
+        let mut targets = vec![0; self.edges.len()];
+        for &(from, to) in &self.edges {
+            targets[next[from]] = to;
+            next[from] += 1;
+        }
+
+        Graph {
+            names: self.names,
+            ids: self.ids,
+            offsets,
+            targets,
+        }
+    }
+}
+
+/// Directed graph with interned node names and adjacency stored as CSR.
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+}
+
+/// Strongly connected components collapsed into single nodes.
+pub struct Condensation {
+    /// Acyclic graph with one node per component
+    pub graph: Graph,
+    /// Component index of every node in the original graph
+    pub component_of: Vec<usize>,
+    /// Original nodes of every component
+    pub members: Vec<Vec<NodeId>>,
+}
+
+impl Graph {
+    pub fn node_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn successors(&self, id: NodeId) -> &[NodeId] {
+        &self.targets[self.offsets[id]..self.offsets[id + 1]]
+    }
+
+    /// Kahn's algorithm. Returns `None` if the graph has a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<NodeId>> {
+        let n = self.node_count();
+        let mut in_degree = vec![0; n];
+        for &to in &self.targets {
+            in_degree[to] += 1;
+        }
+
+        let mut order: Vec<NodeId> = (0..n).filter(|&i| in_degree[i] == 0).collect();
+        let mut i = 0;
+        while i < order.len() {
+            let node = order[i];
+            for &next in self.successors(node) {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    order.push(next);
+                }
+            }
+            i += 1;
+        }
+
+        if order.len() == n { Some(order) } else { None }
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.topological_sort().is_none()
+    }
+
+    /// Marks every node that can be reached from `from`, including itself.
+    pub fn reachable_from(&self, from: NodeId) -> Vec<bool> {
+        let mut seen = vec![false; self.node_count()];
+        let mut stack = vec![from];
+        seen[from] = true;
+        while let Some(node) = stack.pop() {
+            for &next in self.successors(node) {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Marks every node that can reach `to`, including itself.
+    pub fn reaching(&self, to: NodeId) -> Vec<bool> {
+        let mut predecessors = vec![Vec::new(); self.node_count()];
+        for from in 0..self.node_count() {
+            for &next in self.successors(from) {
+                predecessors[next].push(from);
+            }
+        }
+
+        let mut seen = vec![false; self.node_count()];
+        let mut stack = vec![to];
+        seen[to] = true;
+        while let Some(node) = stack.pop() {
+            for &previous in &predecessors[node] {
+                if !seen[previous] {
+                    seen[previous] = true;
+                    stack.push(previous);
+                }
+            }
+        }
+        seen
+    }
+
+    pub fn path_count(&self, from: NodeId, to: NodeId) -> usize {
+        self.path_count_via(from, to, &[])
+    }
+
+    /// Counts the paths from `from` to `to` that visit every node in `via`.
+    ///
+    /// Panics if a cycle lies on a path to `to`, since the count would be
+    /// infinite. Cycles that cannot reach `to` are skipped.
+    pub fn path_count_via(&self, from: NodeId, to: NodeId, via: &[NodeId]) -> usize {
+        if via.len() > 16 {
+            panic!("Too many via nodes: {}", via.len());
+        }
+
+        let mut via_bits = vec![0usize; self.node_count()];
+        for (bit, &node) in via.iter().enumerate() {
+            via_bits[node] |= 1 << bit;
+        }
+
+        let mut counter = PathCounter {
+            graph: self,
+            to,
+            via_bits,
+            all_visited: (1 << via.len()) - 1,
+            masks: 1 << via.len(),
+            memo: vec![None; self.node_count() << via.len()],
+            on_path: vec![false; self.node_count()],
+            reaches_to: self.reaching(to),
+        };
+        if !counter.reaches_to[from] {
+            return 0;
+        }
+        let visited = counter.via_bits[from];
+        counter.count(from, visited)
+    }
+
+    /// Tarjan's algorithm. Components are returned in reverse topological order.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: vec![None; self.node_count()],
+            low_link: vec![0; self.node_count()],
+            on_stack: vec![false; self.node_count()],
+            stack: Vec::new(),
+            next_index: 0,
+            components: Vec::new(),
+        };
+        for node in 0..self.node_count() {
+            if tarjan.index[node].is_none() {
+                tarjan.visit(node);
+            }
+        }
+        tarjan.components
+    }
+
+    pub fn condensation(&self) -> Condensation {
+        let mut members = self.strongly_connected_components();
+        members.reverse();
+
+        let mut component_of = vec![0; self.node_count()];
+        for (component, nodes) in members.iter().enumerate() {
+            for &node in nodes {
+                component_of[node] = component;
+            }
+        }
+
+        let mut builder = GraphBuilder::new();
+        for nodes in &members {
+            let names: Vec<&str> = nodes.iter().map(|&node| self.name(node)).collect();
+            // Names may contain '+' themselves, so they must not be interned
+            builder.add_node(names.join("+"));
+        }
+
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for from in 0..self.node_count() {
+            for &to in self.successors(from) {
+                let edge = (component_of[from], component_of[to]);
+                if edge.0 != edge.1 {
+                    edges.push(edge);
+                }
+            }
+        }
+        edges.sort_unstable();
+        edges.dedup();
+        for (from, to) in edges {
+            builder.add_edge(from, to);
+        }
+
+        Condensation {
+            graph: builder.build(),
+            component_of,
+            members,
+        }
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for node in 0..self.node_count() {
+            dot.push_str(&format!("    {:?};\n", self.name(node)));
+        }
+        for from in 0..self.node_count() {
+            for &to in self.successors(from) {
+                dot.push_str(&format!(
+                    "    {:?} -> {:?};\n",
+                    self.name(from),
+                    self.name(to)
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+struct PathCounter<'a> {
+    graph: &'a Graph,
+    to: NodeId,
+    via_bits: Vec<usize>,
+    all_visited: usize,
+    masks: usize,
+    memo: Vec<Option<usize>>,
+    on_path: Vec<bool>,
+    /// Nodes with a path to `to`, the others add nothing to the count
+    reaches_to: Vec<bool>,
+}
+
+impl PathCounter<'_> {
+    fn count(&mut self, node: NodeId, visited: usize) -> usize {
+        if node == self.to {
+            return if visited == self.all_visited { 1 } else { 0 };
+        }
+
+        let key = node * self.masks + visited;
+        if let Some(count) = self.memo[key] {
+            return count;
+        }
+
+        if self.on_path[node] {
+            panic!("Cycle through node {}", self.graph.name(node));
+        }
+        self.on_path[node] = true;
+
+        let mut total = 0;
+        for &next in self.graph.successors(node) {
+            if self.reaches_to[next] {
+                total += self.count(next, visited | self.via_bits[next]);
+            }
+        }
+
+        self.on_path[node] = false;
+        self.memo[key] = Some(total);
+        total
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a Graph,
+    index: Vec<Option<usize>>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<NodeId>,
+    next_index: usize,
+    components: Vec<Vec<NodeId>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, node: NodeId) {
+        self.index[node] = Some(self.next_index);
+        self.low_link[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for &next in self.graph.successors(node) {
+            match self.index[next] {
+                None => {
+                    self.visit(next);
+                    self.low_link[node] = self.low_link[node].min(self.low_link[next]);
+                }
+                Some(index) if self.on_stack[next] => {
+                    self.low_link[node] = self.low_link[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_link[node]) == self.index[node] {
+            let mut component = Vec::new();
+            loop {
+                let member = self.stack.pop().expect("Tarjan stack underflow");
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(edges: &[(&str, &str)]) -> Graph {
+        let mut builder = GraphBuilder::new();
+        for &(from, to) in edges {
+            let from = builder.node(from);
+            let to = builder.node(to);
+            builder.add_edge(from, to);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_interning_and_successors() {
+        let graph = build(&[("a", "b"), ("a", "c"), ("b", "c")]);
+        assert_eq!(3, graph.node_count());
+        assert_eq!(3, graph.edge_count());
+        let a = graph.id("a").unwrap();
+        let names: Vec<&str> = graph.successors(a).iter().map(|&n| graph.name(n)).collect();
+        assert_eq!(vec!["b", "c"], names);
+        assert_eq!(None, graph.id("d"));
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let graph = build(&[("c", "d"), ("a", "b"), ("b", "c"), ("a", "c")]);
+        let order = graph.topological_sort().unwrap();
+        let position = |name: &str| order.iter().position(|&n| n == graph.id(name).unwrap());
+        assert!(position("a") < position("b"));
+        assert!(position("b") < position("c"));
+        assert!(position("c") < position("d"));
+        assert!(!graph.has_cycle());
+    }
+
+    #[test]
+    fn test_cycle() {
+        let graph = build(&[("a", "b"), ("b", "c"), ("c", "a")]);
+        assert!(graph.has_cycle());
+        assert_eq!(None, graph.topological_sort());
+    }
+
+    #[test]
+    fn test_reachable_from() {
+        let graph = build(&[("a", "b"), ("c", "a")]);
+        let reachable = graph.reachable_from(graph.id("a").unwrap());
+        assert!(reachable[graph.id("a").unwrap()]);
+        assert!(reachable[graph.id("b").unwrap()]);
+        assert!(!reachable[graph.id("c").unwrap()]);
+    }
+
+    #[test]
+    fn test_path_count_via() {
+        // Diamond a -> {b, c} -> d -> e, plus a shortcut a -> d
+        let graph = build(&[
+            ("a", "b"),
+            ("a", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("a", "d"),
+            ("d", "e"),
+        ]);
+        let id = |name: &str| graph.id(name).unwrap();
+        assert_eq!(3, graph.path_count(id("a"), id("e")));
+        assert_eq!(1, graph.path_count_via(id("a"), id("e"), &[id("b")]));
+        assert_eq!(
+            0,
+            graph.path_count_via(id("a"), id("e"), &[id("b"), id("c")])
+        );
+        assert_eq!(0, graph.path_count(id("e"), id("a")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cycle")]
+    fn test_path_count_cycle() {
+        let graph = build(&[("a", "b"), ("b", "a"), ("b", "c")]);
+        graph.path_count(graph.id("a").unwrap(), graph.id("c").unwrap());
+    }
+
+    #[test]
+    fn test_path_count_dead_end_cycle() {
+        // The cycle c <-> d cannot reach e, so it does not add any paths
+        let graph = build(&[("a", "b"), ("b", "e"), ("a", "c"), ("c", "d"), ("d", "c")]);
+        let id = |name: &str| graph.id(name).unwrap();
+        assert_eq!(1, graph.path_count(id("a"), id("e")));
+        assert_eq!(0, graph.path_count(id("c"), id("e")));
+        assert_eq!(
+            vec![true, true, true, false, false],
+            graph.reaching(id("e"))
+        );
+    }
+
+    #[test]
+    fn test_condensation() {
+        let graph = build(&[("a", "b"), ("b", "a"), ("b", "c"), ("c", "d"), ("d", "c")]);
+        let condensation = graph.condensation();
+        assert_eq!(2, condensation.graph.node_count());
+        assert_eq!(1, condensation.graph.edge_count());
+        assert!(!condensation.graph.has_cycle());
+        assert_eq!(
+            condensation.component_of[graph.id("a").unwrap()],
+            condensation.component_of[graph.id("b").unwrap()]
+        );
+        assert_eq!(Some(0), condensation.graph.id("a+b"));
+        assert_eq!(Some(1), condensation.graph.id("c+d"));
+    }
+
+    #[test]
+    fn test_condensation_plus_in_name() {
+        // The cycle {a, b} gets the same name as the node "a+b"
+        let graph = build(&[("a", "b"), ("b", "a"), ("a+b", "a"), ("b", "c")]);
+        let condensation = graph.condensation();
+        assert_eq!(3, condensation.members.len());
+        assert_eq!(3, condensation.graph.node_count());
+        assert_eq!(2, condensation.graph.edge_count());
+        let component = condensation.component_of[graph.id("a+b").unwrap()];
+        assert_eq!(
+            vec![condensation.component_of[graph.id("a").unwrap()]],
+            condensation.graph.successors(component)
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = build(&[("a", "b")]);
+        assert_eq!(
+            "digraph {\n    \"a\";\n    \"b\";\n    \"a\" -> \"b\";\n}\n",
+            graph.to_dot()
+        );
+    }
+}
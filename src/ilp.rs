@@ -0,0 +1,333 @@
+//! Small integer linear programs of the form `A·x = b` with `x >= 0`,
+//! minimising `Σx`, where all coefficients and right-hand sides are
+//! non-negative.
+//!
+//! The system is brought to reduced row echelon form with exact integer
+//! arithmetic. Every solution is then fixed by the free (non-pivot) variables,
+//! which are enumerated within their bounds with interval pruning on the pivot
+//! variables and a lower bound on the objective.
+
+use crate::solver::SolveError;
+
+/// Search nodes visited before `min_sum_solution` gives up.
+pub const NODE_LIMIT: u64 = 50_000_000;
+
+/// The `x` minimising `Σx` among non-negative integer solutions of `A·x = b`.
+pub fn min_sum_solution(a: &[Vec<i64>], b: &[i64]) -> Result<Vec<i64>, SolveError> {
+    min_sum_solution_limited(a, b, NODE_LIMIT)
+}
+
+/// Like `min_sum_solution`, but gives up with `SolveError::Unknown` after
+/// visiting `max_nodes` search nodes.
+pub fn min_sum_solution_limited(
+    a: &[Vec<i64>],
+    b: &[i64],
+    max_nodes: u64,
+) -> Result<Vec<i64>, SolveError> {
+    if a.len() != b.len() {
+        panic!("Got {} rows but {} right-hand sides", a.len(), b.len());
+    }
+    let n = a.first().map_or(0, |row| row.len());
+    if a.iter().any(|row| row.len() != n) {
+        panic!("All rows must have the same length");
+    }
+    if a.iter().flatten().chain(b).any(|&v| v < 0) {
+        panic!("Coefficients and right-hand sides must be non-negative");
+    }
+
+    // Every row holds x_j <= b_i / a_ij. A variable in no row does not help the
+    // objective, so it stays 0.
+    let upper: Vec<i128> = (0..n)
+        .map(|j| {
+            a.iter()
+                .zip(b)
+                .filter(|(row, _)| row[j] > 0)
+                .map(|(row, &rhs)| (rhs / row[j]) as i128)
+                .min()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut rows: Vec<Vec<i128>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &rhs)| row.iter().chain([&rhs]).map(|&v| v as i128).collect())
+        .collect();
+    let pivots = row_reduce(&mut rows, n);
+    if rows[pivots.len()..].iter().any(|row| row[n] != 0) {
+        return Err(SolveError::Infeasible);
+    }
+
+    let mut is_pivot = vec![false; n];
+    for &col in &pivots {
+        is_pivot[col] = true;
+    }
+    let free: Vec<usize> = (0..n).filter(|&j| !is_pivot[j]).collect();
+
+    // Scaling the objective by the lcm of the pivots keeps it integral:
+    // scale·Σx = constant + Σ weight_f·x_f over the free variables.
+    let scale = pivots
+        .iter()
+        .enumerate()
+        .fold(1, |l, (i, &col)| lcm(l, rows[i][col]));
+    let constant: i128 = (0..pivots.len())
+        .map(|i| scale / rows[i][pivots[i]] * rows[i][n])
+        .sum();
+    let weights: Vec<i128> = free
+        .iter()
+        .map(|&f| {
+            scale
+                - (0..pivots.len())
+                    .map(|i| scale / rows[i][pivots[i]] * rows[i][f])
+                    .sum::<i128>()
+        })
+        .collect();
+
+    let mut search = Search {
+        rows: &rows,
+        pivots: &pivots,
+        free: &free,
+        upper: &upper,
+        weights: &weights,
+        scale,
+        residual: rows.iter().take(pivots.len()).map(|row| row[n]).collect(),
+        values: vec![0; free.len()],
+        best: None,
+        nodes_left: max_nodes,
+        gave_up: false,
+    };
+    search.search(0, constant);
+
+    if search.gave_up {
+        return Err(SolveError::Unknown(format!(
+            "Gave up after {} search nodes",
+            max_nodes
+        )));
+    }
+    let Some((_, free_values)) = search.best else {
+        return Err(SolveError::Infeasible);
+    };
+    let mut x = vec![0; n];
+    for (&f, &value) in free.iter().zip(&free_values) {
+        x[f] = value as i64;
+    }
+    for (i, &col) in pivots.iter().enumerate() {
+        let rest: i128 = free.iter().map(|&f| rows[i][f] * x[f] as i128).sum();
+        x[col] = ((rows[i][n] - rest) / rows[i][col]) as i64;
+    }
+    Ok(x)
+}
+
+/// Fraction-free reduction to row echelon form where every pivot column is
+/// zero outside its row and every pivot is positive. Returns the pivot column
+/// of each nonzero row; the remaining rows only have a right-hand side left.
+fn row_reduce(rows: &mut [Vec<i128>], cols: usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for col in 0..cols {
+        let row = pivots.len();
+        let Some(found) = (row..rows.len()).find(|&r| rows[r][col] != 0) else {
+            continue;
+        };
+        rows.swap(row, found);
+        if rows[row][col] < 0 {
+            rows[row].iter_mut().for_each(|v| *v = -*v);
+        }
+
+        let pivot_row = rows[row].clone();
+        for (r, other) in rows.iter_mut().enumerate() {
+            let factor = other[col];
+            if r == row || factor == 0 {
+                continue;
+            }
+            for (v, &p) in other.iter_mut().zip(&pivot_row) {
+                *v = *v * pivot_row[col] - p * factor;
+            }
+            let divisor = other.iter().fold(0, |g, &v| gcd(g, v));
+            if divisor > 1 {
+                other.iter_mut().for_each(|v| *v /= divisor);
+            }
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+struct Search<'a> {
+    rows: &'a [Vec<i128>],
+    pivots: &'a [usize],
+    free: &'a [usize],
+    upper: &'a [i128],
+    weights: &'a [i128],
+    scale: i128,
+    /// Right-hand side of each pivot row minus the assigned free variables
+    residual: Vec<i128>,
+    values: Vec<i128>,
+    /// Scaled objective and free variable values of the best solution so far
+    best: Option<(i128, Vec<i128>)>,
+    nodes_left: u64,
+    /// Set when a node was cut off because `nodes_left` ran out
+    gave_up: bool,
+}
+
+impl Search<'_> {
+    fn search(&mut self, depth: usize, objective: i128) {
+        if self.nodes_left == 0 {
+            self.gave_up = true;
+            return;
+        }
+        self.nodes_left -= 1;
+
+        // The unassigned free variables can lower the objective by at most this
+        let lower_bound = objective
+            + (depth..self.free.len())
+                .map(|k| (self.weights[k] * self.upper[self.free[k]]).min(0))
+                .sum::<i128>();
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|(best, _)| lower_bound >= *best)
+        {
+            return;
+        }
+        if !self.pivots_in_range(depth) {
+            return;
+        }
+
+        if depth == self.free.len() {
+            // All pivot variables are fixed now and must be integral
+            let integral = (0..self.pivots.len())
+                .all(|i| self.residual[i] % self.rows[i][self.pivots[i]] == 0);
+            if integral {
+                debug_assert_eq!(0, objective % self.scale);
+                self.best = Some((objective, self.values.clone()));
+            }
+            return;
+        }
+
+        let f = self.free[depth];
+        for value in 0..=self.upper[f] {
+            for i in 0..self.pivots.len() {
+                self.residual[i] -= self.rows[i][f] * value;
+            }
+            self.values[depth] = value;
+            self.search(depth + 1, objective + self.weights[depth] * value);
+            for i in 0..self.pivots.len() {
+                self.residual[i] += self.rows[i][f] * value;
+            }
+        }
+        self.values[depth] = 0;
+    }
+
+    /// Whether every pivot variable can still end up within its bounds, given
+    /// the range the unassigned free variables can contribute.
+    fn pivots_in_range(&self, depth: usize) -> bool {
+        (0..self.pivots.len()).all(|i| {
+            let (mut low, mut high) = (0, 0);
+            for &f in &self.free[depth..] {
+                let contribution = self.rows[i][f] * self.upper[f];
+                low += contribution.min(0);
+                high += contribution.max(0);
+            }
+            // pivot·x = residual - rest, with 0 <= x <= upper
+            let pivot = self.rows[i][self.pivots[i]];
+            let x_upper = self.upper[self.pivots[i]];
+            self.residual[i] - pivot * x_upper <= high && low <= self.residual[i]
+        })
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lcm(a: i128, b: i128) -> i128 {
+    a / gcd(a, b) * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(a: &[Vec<i64>], b: &[i64], expected_sum: Option<i64>) {
+        let x = min_sum_solution(a, b).ok();
+        assert_eq!(expected_sum, x.as_ref().map(|x| x.iter().sum::<i64>()));
+        if let Some(x) = x {
+            assert!(x.iter().all(|&v| v >= 0));
+            for (row, &rhs) in a.iter().zip(b) {
+                let lhs: i64 = row.iter().zip(&x).map(|(c, v)| c * v).sum();
+                assert_eq!(rhs, lhs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unique_solution() {
+        check(&[vec![1, 1], vec![0, 1]], &[5, 2], Some(5));
+    }
+
+    #[test]
+    fn test_prefers_fewer_presses() {
+        // Either the pair button three times, or both singles three times each
+        check(&[vec![1, 1, 0], vec![1, 0, 1]], &[3, 3], Some(3));
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // Inconsistent rows
+        check(&[vec![1, 1], vec![1, 1]], &[2, 3], None);
+        // Only a fractional solution
+        check(&[vec![2]], &[3], None);
+        // Would need a negative press count
+        check(&[vec![1, 1], vec![0, 1]], &[1, 2], None);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let a = vec![vec![1, 1, 1, 1], vec![1, 2, 3, 4]];
+        let b = [20, 50];
+        assert!(min_sum_solution_limited(&a, &b, 1_000).is_ok());
+        assert!(matches!(
+            min_sum_solution_limited(&a, &b, 3),
+            Err(SolveError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_node_limit_boundary() {
+        // The search only needs the root node here
+        assert_eq!(Ok(vec![5]), min_sum_solution_limited(&[vec![1]], &[5], 1));
+        assert!(matches!(
+            min_sum_solution_limited(&[vec![1]], &[5], 0),
+            Err(SolveError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_unused_column() {
+        check(&[vec![1, 0], vec![1, 0]], &[4, 4], Some(4));
+        check(&[vec![0, 0]], &[0], Some(0));
+    }
+
+    #[test]
+    fn test_needs_integrality() {
+        // The rational optimum x0 = 1.5 is not allowed
+        check(&[vec![2, 1]], &[3], Some(2));
+        check(&[vec![3, 2]], &[7], Some(3));
+    }
+
+    #[test]
+    fn test_day10_example() {
+        // Buttons (3) (1,3) (2) (2,3) (0,2) (0,1) with joltages {3,5,4,7}
+        let a = vec![
+            vec![0, 0, 0, 0, 1, 1],
+            vec![0, 1, 0, 0, 0, 1],
+            vec![0, 0, 1, 1, 1, 0],
+            vec![1, 1, 0, 1, 0, 0],
+        ];
+        check(&a, &[3, 5, 4, 7], Some(10));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bignum;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -10,3 +11,12 @@ pub mod day09;
 pub mod day10;
 pub mod day11;
 pub mod day12;
+pub mod geometry;
+pub mod gf2;
+pub mod graph;
+pub mod ilp;
+pub mod packing;
+pub mod parallel;
+pub mod polyomino;
+pub mod solver;
+pub mod space;
@@ -0,0 +1,124 @@
+use std::env;
+use std::fs;
+use std::process;
+
+use aoc_2025::*;
+
+fn run_part(day: u32, part: u32, input: &str) -> Option<String> {
+    let answer = match (day, part) {
+        (1, 1) => day01::part1(input).to_string(),
+        (1, 2) => day01::part2(input).to_string(),
+        (2, 1) => day02::part1(input).to_string(),
+        (2, 2) => day02::part2(input).to_string(),
+        (3, 1) => day03::part1(input).to_string(),
+        (3, 2) => day03::part2(input).to_string(),
+        (4, 1) => day04::part1(input).to_string(),
+        (4, 2) => day04::part2(input).to_string(),
+        (5, 1) => day05::part1(input).to_string(),
+        (5, 2) => day05::part2(input).to_string(),
+        (6, 1) => day06::part1(input).to_string(),
+        (6, 2) => day06::part2(input).to_string(),
+        (7, 1) => day07::part1(input).to_string(),
+        (7, 2) => day07::part2(input).to_string(),
+        (8, 1) => day08::part1(input, 1000).to_string(),
+        (8, 2) => day08::part2(input).to_string(),
+        (9, 1) => day09::part1(input).to_string(),
+        (9, 2) => day09::part2(input).to_string(),
+        (10, 1) => day10::part1(input).to_string(),
+        (10, 2) => day10::part2(input).to_string(),
+        (11, 1) => day11::part1(input).to_string(),
+        (11, 2) => day11::part2(input).to_string(),
+        (12, 1) => day12::part1(input).to_string(),
+        _ => return None,
+    };
+    Some(answer)
+}
+
+fn explain(day: u32, input: &str) -> Option<String> {
+    let explanation = match day {
+        10 => day10::explain(input),
+        12 => day12::explain(input),
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+/// Step by step record of the solution, as CSV or JSON.
+fn trace(day: u32, input: &str, json: bool) -> Option<String> {
+    let trace = match day {
+        1 => {
+            let steps = day01::trace(input, day01::Dial::default());
+            if json {
+                day01::trace_json(&steps)
+            } else {
+                day01::trace_csv(&steps)
+            }
+        }
+        _ => return None,
+    };
+    Some(trace)
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: aoc [run] <day> [<part>] [--explain] [--trace[=csv|json]]");
+    process::exit(1);
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "run") {
+        args.remove(0);
+    }
+
+    let mut explain_requested = false;
+    let mut trace_requested = None;
+    for arg in args.iter().filter(|arg| arg.starts_with("--")) {
+        match arg.as_str() {
+            "--explain" => explain_requested = true,
+            "--trace" | "--trace=csv" => trace_requested = Some(false),
+            "--trace=json" => trace_requested = Some(true),
+            _ => usage(),
+        }
+    }
+    args.retain(|arg| !arg.starts_with("--"));
+
+    let day: u32 = match args.first().map(|arg| arg.parse()) {
+        Some(Ok(day)) => day,
+        _ => usage(),
+    };
+    let parts: Vec<u32> = match args.get(1).map(|arg| arg.parse()) {
+        Some(Ok(part)) => vec![part],
+        Some(Err(_)) => usage(),
+        None => vec![1, 2],
+    };
+
+    let path = format!("inputs/day{:02}/input.txt", day);
+    let input = match fs::read_to_string(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+
+    if explain_requested {
+        match explain(day, &input) {
+            Some(explanation) => print!("{}", explanation),
+            None => println!("No explanation available for day {}", day),
+        }
+    }
+
+    if let Some(json) = trace_requested {
+        match trace(day, &input, json) {
+            Some(trace) => print!("{}", trace),
+            None => println!("No trace available for day {}", day),
+        }
+    }
+
+    for part in parts {
+        match run_part(day, part, &input) {
+            Some(answer) => println!("Part {}: {}", part, answer),
+            None => println!("Part {}: not available", part),
+        }
+    }
+}
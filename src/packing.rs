@@ -0,0 +1,400 @@
+use crate::polyomino::Shape;
+
+/// One present in a packed region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub shape: usize,
+    /// Index into `shapes[shape].orientations()`
+    pub orientation: usize,
+    /// Position of the top-left corner of the orientation's bounding box
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Decides whether `counts[i]` copies of `shapes[i]` fit without overlap in a
+/// `width` x `height` region, allowing rotations and reflections.
+pub fn fits(shapes: &[Shape], counts: &[usize], width: usize, height: usize) -> bool {
+    pack(shapes, counts, width, height).is_some()
+}
+
+/// Like `fits`, but returns where every present goes.
+pub fn pack(
+    shapes: &[Shape],
+    counts: &[usize],
+    width: usize,
+    height: usize,
+) -> Option<Vec<Placement>> {
+    if shapes.len() != counts.len() {
+        panic!("Got {} shapes but {} counts", shapes.len(), counts.len());
+    }
+
+    let needed: usize = shapes
+        .iter()
+        .zip(counts)
+        .map(|(shape, count)| shape.cell_count() * count)
+        .sum();
+    if needed > width * height {
+        return None;
+    }
+
+    let orientations: Vec<Vec<Shape>> = shapes.iter().map(|s| s.orientations()).collect();
+
+    // Loose regions: every present gets a box of its own
+    let presents: usize = counts.iter().sum();
+    let box_width = max_extent(shapes, counts, |(w, _)| w);
+    let box_height = max_extent(shapes, counts, |(_, h)| h);
+    let boxes_per_row = width / box_width;
+    if presents <= boxes_per_row * (height / box_height) {
+        let mut placements = Vec::with_capacity(presents);
+        for (shape, &count) in counts.iter().enumerate() {
+            let orientation = orientations[shape]
+                .iter()
+                .position(|o| *o == shapes[shape])
+                .unwrap();
+            for _ in 0..count {
+                let i = placements.len();
+                placements.push(Placement {
+                    shape,
+                    orientation,
+                    row: (i / boxes_per_row) * box_height,
+                    col: (i % boxes_per_row) * box_width,
+                });
+            }
+        }
+        return Some(placements);
+    }
+
+    // Scanning along the short side keeps the frontier of undecided cells small.
+    // All orientations are tried, so the region can be turned freely.
+    let transposed = width > height;
+    let (width, height) = (width.min(height), width.max(height));
+
+    let mut search = Packer {
+        width,
+        height,
+        orientations,
+        smallest: shapes
+            .iter()
+            .zip(counts)
+            .filter(|&(_, &count)| count > 0)
+            .map(|(shape, _)| shape.cell_count())
+            .min()
+            .unwrap_or(0),
+        remaining: counts.to_vec(),
+        presents_left: presents,
+        filled: vec![false; width * height],
+        slack: width * height - needed,
+        placed: Vec::with_capacity(presents),
+    };
+    if !search.pack(0) {
+        return None;
+    }
+
+    if !transposed {
+        return Some(search.placed);
+    }
+    let placements = search
+        .placed
+        .iter()
+        .map(|p| {
+            let cells = search.orientations[p.shape][p.orientation].cells();
+            let flipped = Shape::new(cells.iter().map(|&(r, c)| (c, r)).collect());
+            Placement {
+                shape: p.shape,
+                orientation: search.orientations[p.shape]
+                    .iter()
+                    .position(|o| *o == flipped)
+                    .unwrap(),
+                row: p.col,
+                col: p.row,
+            }
+        })
+        .collect();
+    Some(placements)
+}
+
+/// Draws the region with `.` for empty cells and a letter per placed present.
+pub fn render(shapes: &[Shape], placements: &[Placement], width: usize, height: usize) -> String {
+    const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+    let mut grid = vec![vec!['.'; width]; height];
+    for (i, placement) in placements.iter().enumerate() {
+        let letter = LETTERS[i % LETTERS.len()] as char;
+        let orientation = &shapes[placement.shape].orientations()[placement.orientation];
+        for &(r, c) in orientation.cells() {
+            grid[placement.row + r as usize][placement.col + c as usize] = letter;
+        }
+    }
+
+    let mut out = String::new();
+    for row in grid {
+        out.extend(row);
+        out.push('\n');
+    }
+    out
+}
+
+fn max_extent(shapes: &[Shape], counts: &[usize], side: fn((i32, i32)) -> i32) -> usize {
+    shapes
+        .iter()
+        .zip(counts)
+        .filter(|&(_, &count)| count > 0)
+        .map(|(shape, _)| side(shape.bounding_box()) as usize)
+        .max()
+        .unwrap_or(1)
+}
+
+/// Backtracking over the cells in reading order: the first undecided cell is
+/// either covered by the top-left cell of a present, or left empty.
+struct Packer {
+    width: usize,
+    height: usize,
+    orientations: Vec<Vec<Shape>>,
+    smallest: usize,
+    remaining: Vec<usize>,
+    presents_left: usize,
+    /// Covered by a present or given up as empty
+    filled: Vec<bool>,
+    /// How many more cells may be left empty
+    slack: usize,
+    placed: Vec<Placement>,
+}
+
+impl Packer {
+    fn pack(&mut self, start: usize) -> bool {
+        if self.presents_left == 0 {
+            return true;
+        }
+
+        let cell = match (start..self.filled.len()).find(|&i| !self.filled[i]) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        let (row, col) = ((cell / self.width) as i32, (cell % self.width) as i32);
+
+        if self.wasted_cells(cell) > self.slack {
+            return false;
+        }
+
+        for shape in 0..self.orientations.len() {
+            if self.remaining[shape] == 0 {
+                continue;
+            }
+            for orientation in 0..self.orientations[shape].len() {
+                let cells = match self.cells_at(shape, orientation, row, col) {
+                    Some(cells) => cells,
+                    None => continue,
+                };
+
+                self.set(&cells, true);
+                self.remaining[shape] -= 1;
+                self.presents_left -= 1;
+                let (anchor_row, anchor_col) = self.orientations[shape][orientation].cells()[0];
+                self.placed.push(Placement {
+                    shape,
+                    orientation,
+                    row: (row - anchor_row) as usize,
+                    col: (col - anchor_col) as usize,
+                });
+
+                if self.pack(cell + 1) {
+                    return true;
+                }
+
+                self.placed.pop();
+                self.remaining[shape] += 1;
+                self.presents_left += 1;
+                self.set(&cells, false);
+            }
+        }
+
+        if self.slack > 0 {
+            self.slack -= 1;
+            self.filled[cell] = true;
+            let packed = self.pack(cell + 1);
+            self.filled[cell] = false;
+            self.slack += 1;
+            return packed;
+        }
+
+        false
+    }
+
+    /// Undecided cells in pockets too small to hold any present. They will end
+    /// up empty, so there must be enough slack for them.
+    fn wasted_cells(&self, start: usize) -> usize {
+        let mut seen = self.filled.clone();
+        let mut wasted = 0;
+        for cell in start..seen.len() {
+            if seen[cell] {
+                continue;
+            }
+            seen[cell] = true;
+            let mut stack = vec![cell];
+            let mut size = 0;
+            while let Some(current) = stack.pop() {
+                size += 1;
+                let (row, col) = (current / self.width, current % self.width);
+                let mut neighbours = Vec::with_capacity(4);
+                if row > 0 {
+                    neighbours.push(current - self.width);
+                }
+                if row + 1 < self.height {
+                    neighbours.push(current + self.width);
+                }
+                if col > 0 {
+                    neighbours.push(current - 1);
+                }
+                if col + 1 < self.width {
+                    neighbours.push(current + 1);
+                }
+                for next in neighbours {
+                    if !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            if size < self.smallest {
+                wasted += size;
+            }
+        }
+        wasted
+    }
+
+    /// Grid indices covered when the first cell of the orientation is put at
+    /// `(row, col)`, or `None` if it leaves the region or overlaps.
+    fn cells_at(&self, shape: usize, orientation: usize, row: i32, col: i32) -> Option<Vec<usize>> {
+        let cells = self.orientations[shape][orientation].cells();
+        let (anchor_row, anchor_col) = cells[0];
+        cells
+            .iter()
+            .map(|&(r, c)| {
+                let r = row + r - anchor_row;
+                let c = col + c - anchor_col;
+                if r < 0 || c < 0 || r as usize >= self.height || c as usize >= self.width {
+                    return None;
+                }
+                let index = r as usize * self.width + c as usize;
+                if self.filled[index] {
+                    None
+                } else {
+                    Some(index)
+                }
+            })
+            .collect()
+    }
+
+    fn set(&mut self, cells: &[usize], value: bool) {
+        for &cell in cells {
+            self.filled[cell] = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area_reject() {
+        let shapes = vec![Shape::parse("##\n##")];
+        assert!(!fits(&shapes, &[5], 4, 4));
+    }
+
+    #[test]
+    fn test_loose_accept() {
+        let shapes = vec![Shape::parse("###\n#..")];
+        assert!(fits(&shapes, &[4], 6, 6));
+    }
+
+    #[test]
+    fn test_tight_fit_needs_rotation() {
+        // Two L-trominoes fill a 2x3 rectangle only if one is turned around
+        let shapes = vec![Shape::parse("##\n#.")];
+        assert!(fits(&shapes, &[2], 3, 2));
+        assert!(fits(&shapes, &[2], 2, 3));
+    }
+
+    #[test]
+    fn test_enough_area_but_no_fit() {
+        // A 1x3 bar has room for 3 cells, but the L does not fit in a line
+        let shapes = vec![Shape::parse("##\n#.")];
+        assert!(!fits(&shapes, &[1], 3, 1));
+
+        // Two T-tetrominoes cannot tile a 4x2 rectangle
+        let shapes = vec![Shape::parse("###\n.#.")];
+        assert!(!fits(&shapes, &[2], 4, 2));
+    }
+
+    /// Checks that the placements cover exactly the requested presents without overlap.
+    fn assert_valid(shapes: &[Shape], counts: &[usize], width: usize, height: usize) {
+        let placements = pack(shapes, counts, width, height).expect("Expected a packing");
+        let mut placed = vec![0; shapes.len()];
+        for p in &placements {
+            placed[p.shape] += 1;
+        }
+        assert_eq!(counts, placed.as_slice());
+
+        let grid = render(shapes, &placements, width, height);
+        let covered = grid.chars().filter(|c| c.is_alphabetic()).count();
+        let needed: usize = shapes
+            .iter()
+            .zip(counts)
+            .map(|(s, c)| s.cell_count() * c)
+            .sum();
+        assert_eq!(needed, covered);
+        assert_eq!(height, grid.lines().count());
+        assert!(grid.lines().all(|line| line.len() == width));
+    }
+
+    #[test]
+    fn test_placements() {
+        let shapes = vec![Shape::parse("##\n#."), Shape::parse("###\n.#.")];
+        assert_valid(&shapes, &[2, 0], 3, 2);
+        assert_valid(&shapes, &[2, 0], 2, 3);
+        assert_valid(&shapes, &[2, 1], 5, 3);
+        assert_valid(&shapes, &[3, 3], 30, 2);
+        assert_valid(&shapes, &[4, 4], 20, 20);
+    }
+
+    #[test]
+    fn test_render() {
+        let shapes = vec![Shape::parse("##\n#.")];
+        let placements = vec![
+            Placement {
+                shape: 0,
+                orientation: shapes[0]
+                    .orientations()
+                    .iter()
+                    .position(|o| *o == shapes[0])
+                    .unwrap(),
+                row: 0,
+                col: 0,
+            },
+            Placement {
+                shape: 0,
+                orientation: shapes[0]
+                    .orientations()
+                    .iter()
+                    .position(|o| *o == shapes[0].rotate().rotate())
+                    .unwrap(),
+                row: 0,
+                col: 1,
+            },
+        ];
+        assert_eq!("AAB\nABB\n", render(&shapes, &placements, 3, 2));
+    }
+
+    #[test]
+    fn test_mixed_shapes() {
+        let shapes = vec![
+            Shape::parse("####"),
+            Shape::parse("##\n##"),
+            Shape::parse("#"),
+        ];
+        assert!(fits(&shapes, &[1, 1, 2], 4, 3));
+        assert!(!fits(&shapes, &[3, 1, 0], 4, 4));
+        assert!(fits(&shapes, &[2, 2, 0], 4, 4));
+    }
+}
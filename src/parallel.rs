@@ -0,0 +1,64 @@
+//! Runs independent jobs on all cores.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Applies `f` to every item on a pool of scoped threads. The results are in
+/// the same order as `items`, no matter which thread finished first.
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+    if workers <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    // Workers take the next unclaimed item, so slow items do not hold up a whole chunk
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= items.len() {
+                            break done;
+                        }
+                        done.push((i, f(&items[i])));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    results.sort_unstable_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        let squares = map(&items, |&x| x * x);
+        assert_eq!(items.iter().map(|x| x * x).collect::<Vec<_>>(), squares);
+    }
+
+    #[test]
+    fn test_empty() {
+        let items: Vec<u64> = Vec::new();
+        assert!(map(&items, |&x| x).is_empty());
+    }
+}
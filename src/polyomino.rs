@@ -0,0 +1,155 @@
+use std::fmt;
+
+/// A set of grid cells, stored as sorted `(row, col)` pairs translated so the
+/// smallest row and column are 0.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape {
+    cells: Vec<(i32, i32)>,
+}
+
+impl Shape {
+    pub fn new(cells: Vec<(i32, i32)>) -> Self {
+        if cells.is_empty() {
+            panic!("A shape needs at least one cell");
+        }
+        let mut shape = Shape { cells };
+        shape.normalize();
+        shape
+    }
+
+    /// Parses rows of `#` (filled) and `.` (empty).
+    pub fn parse(block: &str) -> Self {
+        let mut cells = Vec::new();
+        for (row, line) in block.lines().enumerate() {
+            for (col, c) in line.trim().chars().enumerate() {
+                match c {
+                    '#' => cells.push((row as i32, col as i32)),
+                    '.' => {}
+                    _ => panic!("Unexpected character {} in shape", c),
+                }
+            }
+        }
+        Shape::new(cells)
+    }
+
+    pub fn cells(&self) -> &[(i32, i32)] {
+        &self.cells
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// `(width, height)` of the smallest box around the shape.
+    pub fn bounding_box(&self) -> (i32, i32) {
+        let width = self.cells.iter().map(|&(_, col)| col).max().unwrap() + 1;
+        let height = self.cells.iter().map(|&(row, _)| row).max().unwrap() + 1;
+        (width, height)
+    }
+
+    /// Rotated a quarter turn clockwise.
+    pub fn rotate(&self) -> Shape {
+        Shape::new(self.cells.iter().map(|&(row, col)| (col, -row)).collect())
+    }
+
+    /// Mirrored left to right.
+    pub fn reflect(&self) -> Shape {
+        Shape::new(self.cells.iter().map(|&(row, col)| (row, -col)).collect())
+    }
+
+    /// All distinct rotations and reflections, sorted.
+    pub fn orientations(&self) -> Vec<Shape> {
+        let mut orientations = Vec::with_capacity(8);
+        let mut shape = self.clone();
+        for _ in 0..4 {
+            orientations.push(shape.reflect());
+            shape = shape.rotate();
+            orientations.push(shape.clone());
+        }
+        orientations.sort();
+        orientations.dedup();
+        orientations
+    }
+
+    /// The same representative for all orientations of a shape, so shapes can
+    /// be compared regardless of how they are turned.
+    pub fn canonical(&self) -> Shape {
+        self.orientations().swap_remove(0)
+    }
+
+    fn normalize(&mut self) {
+        let min_row = self.cells.iter().map(|&(row, _)| row).min().unwrap();
+        let min_col = self.cells.iter().map(|&(_, col)| col).min().unwrap();
+        for cell in self.cells.iter_mut() {
+            *cell = (cell.0 - min_row, cell.1 - min_col);
+        }
+        self.cells.sort_unstable();
+        self.cells.dedup();
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.bounding_box();
+        for row in 0..height {
+            for col in 0..width {
+                let c = if self.cells.binary_search(&(row, col)).is_ok() {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let shape = Shape::parse("###\n#..\n###");
+        assert_eq!(7, shape.cell_count());
+        assert_eq!((3, 3), shape.bounding_box());
+        assert_eq!("###\n#..\n###\n", shape.to_string());
+    }
+
+    #[test]
+    fn test_parse_trims_empty_margin() {
+        let shape = Shape::parse("...\n.##\n.#.");
+        assert_eq!((2, 2), shape.bounding_box());
+        assert_eq!(vec![(0, 0), (0, 1), (1, 0)], shape.cells());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let shape = Shape::parse("##.\n.##");
+        assert_eq!(".#\n##\n#.\n", shape.rotate().to_string());
+        assert_eq!(shape, shape.rotate().rotate());
+        assert_eq!(".##\n##.\n", shape.reflect().to_string());
+    }
+
+    #[test]
+    fn test_orientation_counts() {
+        assert_eq!(1, Shape::parse("##\n##").orientations().len());
+        assert_eq!(2, Shape::parse("###").orientations().len());
+        assert_eq!(4, Shape::parse("##\n#.").orientations().len());
+        assert_eq!(4, Shape::parse("###\n.#.").orientations().len());
+        assert_eq!(4, Shape::parse(".##\n##.").orientations().len());
+        assert_eq!(8, Shape::parse("###\n#..").orientations().len());
+        assert_eq!(2, Shape::parse("###\n.#.\n###").orientations().len());
+    }
+
+    #[test]
+    fn test_canonical() {
+        let shape = Shape::parse("###\n#..");
+        for orientation in shape.orientations() {
+            assert_eq!(shape.canonical(), orientation.canonical());
+        }
+        assert_ne!(shape.canonical(), Shape::parse("###\n.#.").canonical());
+    }
+}
@@ -0,0 +1,286 @@
+//! Small integer optimisation problems: integer variables, linear and parity
+//! constraints, and a linear objective to minimise.
+//!
+//! Problems are solved with z3 when the `z3` feature is enabled, and with a
+//! pure-Rust branch and bound search otherwise.
+
+mod search;
+#[cfg(feature = "z3")]
+mod z3_backend;
+
+pub use search::Search;
+#[cfg(feature = "z3")]
+pub use z3_backend::Z3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Var(usize);
+
+impl Var {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Sum of `coefficient * variable` terms plus a constant.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinearExpr {
+    pub terms: Vec<(Var, i64)>,
+    pub constant: i64,
+}
+
+impl LinearExpr {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sum(vars: &[Var]) -> Self {
+        LinearExpr {
+            terms: vars.iter().map(|&var| (var, 1)).collect(),
+            constant: 0,
+        }
+    }
+
+    pub fn add_term(&mut self, var: Var, coefficient: i64) {
+        self.terms.push((var, coefficient));
+    }
+
+    pub fn eval(&self, values: &[i64]) -> i64 {
+        self.constant
+            + self
+                .terms
+                .iter()
+                .map(|&(var, coefficient)| coefficient * values[var.0])
+                .sum::<i64>()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Relation {
+    Eq,
+    Le,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constraint {
+    /// `expr <relation> rhs`
+    Linear {
+        expr: LinearExpr,
+        relation: Relation,
+        rhs: i64,
+    },
+    /// `expr mod 2 == odd as i64`
+    Parity { expr: LinearExpr, odd: bool },
+}
+
+impl Constraint {
+    pub fn is_satisfied(&self, values: &[i64]) -> bool {
+        match self {
+            Constraint::Linear {
+                expr,
+                relation,
+                rhs,
+            } => {
+                let value = expr.eval(values);
+                match relation {
+                    Relation::Eq => value == *rhs,
+                    Relation::Le => value <= *rhs,
+                    Relation::Ge => value >= *rhs,
+                }
+            }
+            Constraint::Parity { expr, odd } => expr.eval(values).rem_euclid(2) == *odd as i64,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub name: String,
+    pub lower: i64,
+    pub upper: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Problem {
+    pub variables: Vec<Variable>,
+    pub constraints: Vec<Constraint>,
+    pub objective: LinearExpr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+    pub values: Vec<i64>,
+    pub objective: i64,
+}
+
+impl Solution {
+    pub fn value(&self, var: Var) -> i64 {
+        self.values[var.0]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolveError {
+    Infeasible,
+    /// The backend gave up, e.g. on a timeout or an unsupported problem
+    Unknown(String),
+}
+
+pub trait Backend {
+    fn name(&self) -> &'static str;
+
+    fn solve(&self, problem: &Problem) -> Result<Solution, SolveError>;
+}
+
+impl Problem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn int_var(&mut self, name: &str, lower: i64, upper: Option<i64>) -> Var {
+        self.variables.push(Variable {
+            name: name.to_string(),
+            lower,
+            upper,
+        });
+        Var(self.variables.len() - 1)
+    }
+
+    pub fn equal(&mut self, expr: LinearExpr, rhs: i64) {
+        self.linear(expr, Relation::Eq, rhs);
+    }
+
+    pub fn at_most(&mut self, expr: LinearExpr, rhs: i64) {
+        self.linear(expr, Relation::Le, rhs);
+    }
+
+    pub fn at_least(&mut self, expr: LinearExpr, rhs: i64) {
+        self.linear(expr, Relation::Ge, rhs);
+    }
+
+    pub fn parity(&mut self, expr: LinearExpr, odd: bool) {
+        self.constraints.push(Constraint::Parity { expr, odd });
+    }
+
+    pub fn minimize(&mut self, objective: LinearExpr) {
+        self.objective = objective;
+    }
+
+    /// Whether `values` respects all bounds and constraints.
+    pub fn is_feasible(&self, values: &[i64]) -> bool {
+        values.len() == self.variables.len()
+            && self.variables.iter().zip(values).all(|(variable, &value)| {
+                value >= variable.lower && variable.upper.is_none_or(|upper| value <= upper)
+            })
+            && self.constraints.iter().all(|c| c.is_satisfied(values))
+    }
+
+    /// Solves with z3 if the `z3` feature is enabled, or the native search otherwise.
+    pub fn solve(&self) -> Result<Solution, SolveError> {
+        default_backend().solve(self)
+    }
+
+    fn linear(&mut self, expr: LinearExpr, relation: Relation, rhs: i64) {
+        self.constraints.push(Constraint::Linear {
+            expr,
+            relation,
+            rhs,
+        });
+    }
+}
+
+#[cfg(feature = "z3")]
+pub fn default_backend() -> &'static dyn Backend {
+    &Z3
+}
+
+#[cfg(not(feature = "z3"))]
+pub fn default_backend() -> &'static dyn Backend {
+    &Search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backends() -> Vec<&'static dyn Backend> {
+        #[cfg(feature = "z3")]
+        return vec![&Search, &Z3];
+        #[cfg(not(feature = "z3"))]
+        return vec![&Search];
+    }
+
+    #[test]
+    fn test_equalities() {
+        // x + y = 5, y + z = 7, min x + y + z -> x = 0, y = 5, z = 2
+        let mut problem = Problem::new();
+        let x = problem.int_var("x", 0, None);
+        let y = problem.int_var("y", 0, None);
+        let z = problem.int_var("z", 0, None);
+        problem.equal(LinearExpr::sum(&[x, y]), 5);
+        problem.equal(LinearExpr::sum(&[y, z]), 7);
+        problem.minimize(LinearExpr::sum(&[x, y, z]));
+
+        for backend in backends() {
+            let solution = backend.solve(&problem).unwrap();
+            assert_eq!(7, solution.objective);
+            assert_eq!(vec![0, 5, 2], solution.values);
+        }
+    }
+
+    #[test]
+    fn test_parity() {
+        // Toggling: a flips lights 0 and 1, b flips 1, c flips 0. Want only light 1 on.
+        let mut problem = Problem::new();
+        let a = problem.int_var("a", 0, Some(1));
+        let b = problem.int_var("b", 0, Some(1));
+        let c = problem.int_var("c", 0, Some(1));
+        problem.parity(LinearExpr::sum(&[a, c]), false);
+        problem.parity(LinearExpr::sum(&[a, b]), true);
+        problem.minimize(LinearExpr::sum(&[a, b, c]));
+
+        for backend in backends() {
+            let solution = backend.solve(&problem).unwrap();
+            assert_eq!(1, solution.objective);
+            assert_eq!(1, solution.value(b));
+            assert!(problem.is_feasible(&solution.values));
+        }
+    }
+
+    #[test]
+    fn test_inequalities_and_weights() {
+        // 2x + 3y >= 12, x <= 4, min 3x + 4y -> x = 0, y = 4 (16), beating x = 3, y = 2 (17)
+        let mut problem = Problem::new();
+        let x = problem.int_var("x", 0, Some(10));
+        let y = problem.int_var("y", 0, Some(10));
+        let mut expr = LinearExpr::new();
+        expr.add_term(x, 2);
+        expr.add_term(y, 3);
+        problem.at_least(expr, 12);
+        problem.at_most(LinearExpr::sum(&[x]), 4);
+        let mut objective = LinearExpr::new();
+        objective.add_term(x, 3);
+        objective.add_term(y, 4);
+        problem.minimize(objective);
+
+        for backend in backends() {
+            let solution = backend.solve(&problem).unwrap();
+            assert_eq!(16, solution.objective);
+            assert_eq!(vec![0, 4], solution.values);
+        }
+    }
+
+    #[test]
+    fn test_infeasible() {
+        let mut problem = Problem::new();
+        let x = problem.int_var("x", 0, Some(1));
+        let mut double = LinearExpr::new();
+        double.add_term(x, 2);
+        problem.equal(double, 3);
+        problem.minimize(LinearExpr::sum(&[x]));
+
+        for backend in backends() {
+            assert_eq!(Err(SolveError::Infeasible), backend.solve(&problem));
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use super::{Backend, Constraint, LinearExpr, Problem, Relation, Solution, SolveError};
+
+/// Pure-Rust branch and bound over bounded integer variables.
+///
+/// Variables without an explicit upper bound must get one from the linear
+/// constraints, e.g. `x + y = 5` with `x, y >= 0`.
+pub struct Search;
+
+/// A constraint in the form `sum(coefficient * x) <= bound`.
+struct Row {
+    terms: Vec<(usize, i64)>,
+    bound: i64,
+}
+
+fn rows(problem: &Problem) -> Vec<Row> {
+    let row = |expr: &LinearExpr, sign: i64, rhs: i64| Row {
+        terms: expr
+            .terms
+            .iter()
+            .map(|&(var, coefficient)| (var.index(), sign * coefficient))
+            .collect(),
+        bound: sign * (rhs - expr.constant),
+    };
+
+    let mut rows = Vec::new();
+    for constraint in &problem.constraints {
+        if let Constraint::Linear {
+            expr,
+            relation,
+            rhs,
+        } = constraint
+        {
+            if *relation != Relation::Ge {
+                rows.push(row(expr, 1, *rhs));
+            }
+            if *relation != Relation::Le {
+                rows.push(row(expr, -1, *rhs));
+            }
+        }
+    }
+    rows
+}
+
+fn floor_div(a: i64, b: i64) -> i64 {
+    if b > 0 {
+        a.div_euclid(b)
+    } else {
+        (-a).div_euclid(-b)
+    }
+}
+
+fn ceil_div(a: i64, b: i64) -> i64 {
+    -floor_div(-a, b)
+}
+
+/// Smallest value of `coefficient * x` for x in `lower..=upper`, `None` if unbounded.
+fn term_min(coefficient: i64, lower: i64, upper: Option<i64>) -> Option<i64> {
+    if coefficient >= 0 {
+        Some(coefficient * lower)
+    } else {
+        upper.map(|upper| coefficient * upper)
+    }
+}
+
+/// Narrows `lower..=upper` of `var` so that `row` can still be satisfied, given
+/// the ranges of the other variables.
+fn narrow(row: &Row, var: usize, lower: &[i64], upper: &[Option<i64>]) -> (i64, Option<i64>) {
+    let mut rest = 0;
+    let mut coefficient = 0;
+    for &(other, c) in &row.terms {
+        if other == var {
+            coefficient += c;
+            continue;
+        }
+        match term_min(c, lower[other], upper[other]) {
+            Some(min) => rest += min,
+            None => return (lower[var], upper[var]),
+        }
+    }
+
+    // coefficient * x <= bound - rest
+    let slack = row.bound - rest;
+    match coefficient {
+        c if c > 0 => {
+            let limit = floor_div(slack, c);
+            (lower[var], Some(upper[var].map_or(limit, |u| u.min(limit))))
+        }
+        c if c < 0 => (lower[var].max(ceil_div(slack, c)), upper[var]),
+        _ => (lower[var], upper[var]),
+    }
+}
+
+/// Tightens the variable bounds until nothing changes.
+fn propagate(rows: &[Row], lower: &mut [i64], upper: &mut [Option<i64>]) -> Result<(), SolveError> {
+    for _ in 0..100 {
+        let mut changed = false;
+        for row in rows {
+            for &(var, _) in &row.terms {
+                let (new_lower, new_upper) = narrow(row, var, lower, upper);
+                if new_lower != lower[var] || new_upper != upper[var] {
+                    lower[var] = new_lower;
+                    upper[var] = new_upper;
+                    changed = true;
+                }
+                if upper[var].is_some_and(|u| u < lower[var]) {
+                    return Err(SolveError::Infeasible);
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    Ok(())
+}
+
+struct BranchAndBound<'a> {
+    problem: &'a Problem,
+    rows: Vec<Row>,
+    rows_of: Vec<Vec<usize>>,
+    /// Variables in the order they are branched on
+    order: Vec<usize>,
+    /// Parity constraints that become fully assigned at each depth
+    parity_at: Vec<Vec<usize>>,
+    objective: Vec<i64>,
+    lower: Vec<i64>,
+    upper: Vec<Option<i64>>,
+    values: Vec<i64>,
+    best: Option<(i64, Vec<i64>)>,
+}
+
+impl BranchAndBound<'_> {
+    fn search(&mut self, depth: usize, cost: i64) {
+        if depth == self.order.len() {
+            if self.best.as_ref().is_none_or(|(best, _)| cost < *best) {
+                self.best = Some((cost, self.values.clone()));
+            }
+            return;
+        }
+
+        let var = self.order[depth];
+        let (saved_lower, saved_upper) = (self.lower[var], self.upper[var]);
+
+        // Narrow the range of this variable given everything assigned so far
+        let mut lower = saved_lower;
+        let mut upper = saved_upper.unwrap();
+        for &row in &self.rows_of[var] {
+            let (l, u) = narrow(&self.rows[row], var, &self.lower, &self.upper);
+            lower = lower.max(l);
+            upper = upper.min(u.unwrap());
+        }
+
+        let remaining_min: i64 = self.order[depth + 1..]
+            .iter()
+            .map(|&v| term_min(self.objective[v], self.lower[v], self.upper[v]).unwrap())
+            .sum();
+
+        let step: i64 = if self.objective[var] < 0 { -1 } else { 1 };
+        let mut value = if step > 0 { lower } else { upper };
+        while lower <= value && value <= upper {
+            let new_cost = cost + self.objective[var] * value;
+            if self
+                .best
+                .as_ref()
+                .is_some_and(|(best, _)| new_cost + remaining_min >= *best)
+            {
+                // Values only get more expensive from here
+                break;
+            }
+
+            self.values[var] = value;
+            self.lower[var] = value;
+            self.upper[var] = Some(value);
+            if self.is_consistent(var, depth) {
+                self.search(depth + 1, new_cost);
+            }
+            value += step;
+        }
+
+        self.lower[var] = saved_lower;
+        self.upper[var] = saved_upper;
+    }
+
+    fn is_consistent(&self, var: usize, depth: usize) -> bool {
+        let rows_ok = self.rows_of[var].iter().all(|&row| {
+            let row = &self.rows[row];
+            let min: i64 = row
+                .terms
+                .iter()
+                .map(|&(v, c)| term_min(c, self.lower[v], self.upper[v]).unwrap())
+                .sum();
+            min <= row.bound
+        });
+
+        rows_ok
+            && self.parity_at[depth]
+                .iter()
+                .all(|&c| self.problem.constraints[c].is_satisfied(&self.values))
+    }
+}
+
+impl Backend for Search {
+    fn name(&self) -> &'static str {
+        "native search"
+    }
+
+    fn solve(&self, problem: &Problem) -> Result<Solution, SolveError> {
+        let n = problem.variables.len();
+        let rows = rows(problem);
+        let mut lower: Vec<i64> = problem.variables.iter().map(|v| v.lower).collect();
+        let mut upper: Vec<Option<i64>> = problem.variables.iter().map(|v| v.upper).collect();
+        propagate(&rows, &mut lower, &mut upper)?;
+
+        if let Some(variable) = (0..n).find(|&v| upper[v].is_none()) {
+            return Err(SolveError::Unknown(format!(
+                "Variable {} is unbounded",
+                problem.variables[variable].name
+            )));
+        }
+
+        let mut rows_of = vec![Vec::new(); n];
+        for (i, row) in rows.iter().enumerate() {
+            for &(var, _) in &row.terms {
+                if !rows_of[var].contains(&i) {
+                    rows_of[var].push(i);
+                }
+            }
+        }
+
+        // Small domains first, so that constrained variables prune early
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&v| (upper[v].unwrap() - lower[v], v));
+
+        let mut depth_of = vec![0; n];
+        for (depth, &var) in order.iter().enumerate() {
+            depth_of[var] = depth;
+        }
+
+        let mut parity_at = vec![Vec::new(); n.max(1)];
+        for (i, constraint) in problem.constraints.iter().enumerate() {
+            if let Constraint::Parity { expr, .. } = constraint {
+                let depth = expr
+                    .terms
+                    .iter()
+                    .map(|(var, _)| depth_of[var.index()])
+                    .max();
+                match depth {
+                    Some(depth) => parity_at[depth].push(i),
+                    None if !constraint.is_satisfied(&vec![0; n]) => {
+                        return Err(SolveError::Infeasible);
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        let mut objective = vec![0; n];
+        for &(var, coefficient) in &problem.objective.terms {
+            objective[var.index()] += coefficient;
+        }
+
+        let mut search = BranchAndBound {
+            problem,
+            rows,
+            rows_of,
+            order,
+            parity_at,
+            objective,
+            lower,
+            upper,
+            values: vec![0; n],
+            best: None,
+        };
+        search.search(0, problem.objective.constant);
+
+        match search.best {
+            Some((objective, values)) => Ok(Solution { values, objective }),
+            None => Err(SolveError::Infeasible),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use z3::Optimize;
+use z3::ast;
+
+use super::{Backend, Constraint, LinearExpr, Problem, Relation, Solution, SolveError};
+
+/// Hands the problem to z3's `Optimize`.
+pub struct Z3;
+
+fn to_ast(expr: &LinearExpr, vars: &[ast::Int]) -> ast::Int {
+    let mut terms: Vec<ast::Int> = vec![ast::Int::from_i64(expr.constant)];
+    for &(var, coefficient) in &expr.terms {
+        terms.push(&vars[var.index()] * coefficient);
+    }
+    ast::Int::add(&terms)
+}
+
+impl Backend for Z3 {
+    fn name(&self) -> &'static str {
+        "z3"
+    }
+
+    fn solve(&self, problem: &Problem) -> Result<Solution, SolveError> {
+        let optimizer = Optimize::new();
+
+        let vars: Vec<ast::Int> = problem
+            .variables
+            .iter()
+            .map(|variable| {
+                let var = ast::Int::new_const(variable.name.as_str());
+                optimizer.assert(&var.ge(variable.lower));
+                if let Some(upper) = variable.upper {
+                    optimizer.assert(&var.le(upper));
+                }
+                var
+            })
+            .collect();
+
+        for constraint in &problem.constraints {
+            match constraint {
+                Constraint::Linear {
+                    expr,
+                    relation,
+                    rhs,
+                } => {
+                    let expr = to_ast(expr, &vars);
+                    optimizer.assert(&match relation {
+                        Relation::Eq => expr.eq(*rhs),
+                        Relation::Le => expr.le(*rhs),
+                        Relation::Ge => expr.ge(*rhs),
+                    });
+                }
+                Constraint::Parity { expr, odd } => {
+                    let expr = to_ast(expr, &vars);
+                    optimizer.assert(&expr.modulo(2).eq(if *odd { 1 } else { 0 }));
+                }
+            }
+        }
+
+        optimizer.minimize(&to_ast(&problem.objective, &vars));
+
+        match optimizer.check(&[]) {
+            z3::SatResult::Sat => {}
+            z3::SatResult::Unsat => return Err(SolveError::Infeasible),
+            z3::SatResult::Unknown => {
+                let reason = optimizer
+                    .get_reason_unknown()
+                    .unwrap_or_else(|| "unknown".to_string());
+                return Err(SolveError::Unknown(reason));
+            }
+        }
+
+        let model = optimizer.get_model().unwrap();
+        let values: Vec<i64> = vars
+            .iter()
+            .map(|var| model.eval(var, true).unwrap().as_i64().unwrap())
+            .collect();
+        let objective = problem.objective.eval(&values);
+
+        Ok(Solution { values, objective })
+    }
+}
@@ -0,0 +1,298 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Self {
+        Point3 { x, y, z }
+    }
+
+    /// Squared euclidean distance. Exact, so it can be compared without ties
+    /// introduced by rounding a square root.
+    pub fn distance2(&self, other: &Point3) -> i64 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        let dz = self.z - other.z;
+        dx * dx + dy * dy + dz * dz
+    }
+
+    fn axis(&self, axis: usize) -> i64 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+}
+
+/// Static k-d tree over a set of points. Queries return indices into the
+/// slice the tree was built from, ordered by distance and then index.
+pub struct KdTree {
+    points: Vec<Point3>,
+    // Implicit tree: the median of order[lo..hi] sits at (lo + hi) / 2
+    order: Vec<usize>,
+}
+
+impl KdTree {
+    pub fn new(points: &[Point3]) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut order, 0);
+        KdTree {
+            points: points.to_vec(),
+            order,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The `k` points closest to `query`, nearest first.
+    pub fn nearest(&self, query: &Point3, k: usize) -> Vec<usize> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut best: BinaryHeap<(i64, usize)> = BinaryHeap::new();
+        self.nearest_in(query, k, 0, self.order.len(), 0, &mut best);
+        best.into_sorted_vec().into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// All points with a squared distance of at most `radius2` to `query`, nearest first.
+    pub fn within(&self, query: &Point3, radius2: i64) -> Vec<usize> {
+        let mut found: Vec<(i64, usize)> = Vec::new();
+        self.within_in(query, radius2, 0, self.order.len(), 0, &mut found);
+        found.sort_unstable();
+        found.into_iter().map(|(_, i)| i).collect()
+    }
+
+    fn nearest_in(
+        &self,
+        query: &Point3,
+        k: usize,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        best: &mut BinaryHeap<(i64, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let index = self.order[mid];
+        let point = &self.points[index];
+
+        let candidate = (query.distance2(point), index);
+        if best.len() < k {
+            best.push(candidate);
+        } else if candidate < *best.peek().unwrap() {
+            best.pop();
+            best.push(candidate);
+        }
+
+        let diff = query.axis(axis) - point.axis(axis);
+        let next_axis = (axis + 1) % 3;
+        let (near, far) = if diff < 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+
+        self.nearest_in(query, k, near.0, near.1, next_axis, best);
+        // Ties at the splitting plane may still win on index, so only prune strictly farther
+        if best.len() < k || diff * diff <= best.peek().unwrap().0 {
+            self.nearest_in(query, k, far.0, far.1, next_axis, best);
+        }
+    }
+
+    fn within_in(
+        &self,
+        query: &Point3,
+        radius2: i64,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        found: &mut Vec<(i64, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let index = self.order[mid];
+        let point = &self.points[index];
+
+        let distance2 = query.distance2(point);
+        if distance2 <= radius2 {
+            found.push((distance2, index));
+        }
+
+        let diff = query.axis(axis) - point.axis(axis);
+        let next_axis = (axis + 1) % 3;
+        if diff <= 0 || diff * diff <= radius2 {
+            self.within_in(query, radius2, lo, mid, next_axis, found);
+        }
+        if diff >= 0 || diff * diff <= radius2 {
+            self.within_in(query, radius2, mid + 1, hi, next_axis, found);
+        }
+    }
+}
+
+fn build(points: &[Point3], order: &mut [usize], axis: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by_key(mid, |&i| points[i].axis(axis));
+    let (left, right) = order.split_at_mut(mid);
+    build(points, left, (axis + 1) % 3);
+    build(points, &mut right[1..], (axis + 1) % 3);
+}
+
+/// Iterator over all pairs `(i, j)` with `i < j`, shortest distance first.
+///
+/// Pairs are produced lazily: every point keeps a cursor into its own list of
+/// neighbours, and a heap merges those lists.
+pub struct ClosestPairs {
+    tree: KdTree,
+    neighbours: Vec<Vec<usize>>,
+    cursor: Vec<usize>,
+    heap: BinaryHeap<Reverse<(i64, usize, usize)>>,
+}
+
+pub fn closest_pairs(points: &[Point3]) -> ClosestPairs {
+    let mut pairs = ClosestPairs {
+        tree: KdTree::new(points),
+        neighbours: vec![Vec::new(); points.len()],
+        cursor: vec![0; points.len()],
+        heap: BinaryHeap::new(),
+    };
+    for i in 0..points.len() {
+        pairs.push_next(i);
+    }
+    pairs
+}
+
+impl ClosestPairs {
+    fn push_next(&mut self, i: usize) {
+        let n = self.tree.len();
+        if self.cursor[i] >= self.neighbours[i].len() {
+            let fetched = self.neighbours[i].len();
+            if fetched >= n {
+                return;
+            }
+            // Fetch neighbours in doubling batches, one extra for the point itself
+            let k = ((fetched + 1) * 2).min(n);
+            self.neighbours[i] = self.tree.nearest(&self.tree.points[i], k);
+        }
+
+        let j = self.neighbours[i][self.cursor[i]];
+        self.cursor[i] += 1;
+        if j == i {
+            self.push_next(i);
+            return;
+        }
+        let distance2 = self.tree.points[i].distance2(&self.tree.points[j]);
+        self.heap.push(Reverse((distance2, i, j)));
+    }
+}
+
+impl Iterator for ClosestPairs {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        // Every pair shows up once from each end; keep the one where i < j
+        while let Some(Reverse((_, i, j))) = self.heap.pop() {
+            self.push_next(i);
+            if i < j {
+                return Some((i, j));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_points() -> Vec<Point3> {
+        let mut points = Vec::new();
+        for i in 0..60i64 {
+            // Scrambled but deterministic coordinates, with some duplicates
+            points.push(Point3::new((i * 37) % 17, (i * 11) % 13, (i * 7) % 5));
+        }
+        points
+    }
+
+    fn brute_sorted(points: &[Point3], query: &Point3) -> Vec<(i64, usize)> {
+        let mut all: Vec<(i64, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (query.distance2(p), i))
+            .collect();
+        all.sort_unstable();
+        all
+    }
+
+    #[test]
+    fn test_distance2() {
+        let a = Point3::new(1, 2, 3);
+        let b = Point3::new(4, 6, 3);
+        assert_eq!(25, a.distance2(&b));
+        // isqrt would tie these
+        let origin = Point3::new(0, 0, 0);
+        assert!(origin.distance2(&Point3::new(2, 0, 0)) < origin.distance2(&Point3::new(2, 1, 0)));
+    }
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let points = grid_points();
+        let tree = KdTree::new(&points);
+        for query in [Point3::new(3, 4, 2), Point3::new(-5, 20, 1), points[7]] {
+            let expected: Vec<usize> = brute_sorted(&points, &query)
+                .into_iter()
+                .take(9)
+                .map(|(_, i)| i)
+                .collect();
+            assert_eq!(expected, tree.nearest(&query, 9));
+        }
+        assert_eq!(points.len(), tree.nearest(&points[0], 1000).len());
+    }
+
+    #[test]
+    fn test_within_matches_brute_force() {
+        let points = grid_points();
+        let tree = KdTree::new(&points);
+        let query = Point3::new(8, 6, 2);
+        let expected: Vec<usize> = brute_sorted(&points, &query)
+            .into_iter()
+            .filter(|&(d, _)| d <= 10)
+            .map(|(_, i)| i)
+            .collect();
+        assert_eq!(expected, tree.within(&query, 10));
+    }
+
+    #[test]
+    fn test_closest_pairs() {
+        let points = grid_points();
+        let mut expected: Vec<(i64, usize, usize)> = Vec::new();
+        for i in 0..points.len() {
+            for j in (i + 1)..points.len() {
+                expected.push((points[i].distance2(&points[j]), i, j));
+            }
+        }
+        expected.sort_unstable();
+
+        let expected: Vec<(usize, usize)> = expected.into_iter().map(|(_, i, j)| (i, j)).collect();
+        assert_eq!(expected, closest_pairs(&points).collect::<Vec<_>>());
+    }
+}